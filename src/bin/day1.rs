@@ -1,66 +1,478 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    time::Instant,
+};
+
 fn main() {
     let input = include_str!("../../input/day1.txt");
-    println!("Part 1: {}", part1(input));
-    println!("Part 2: {}", part2(input));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let language = args
+        .iter()
+        .position(|arg| arg == "--language")
+        .and_then(|index| args.get(index + 1))
+        .map(String::as_str);
+    let ignore_case = args.iter().any(|arg| arg == "--ignore-case");
+    let words = vocabulary(language.unwrap_or("en")).unwrap_or_else(|| {
+        let known = VOCABULARIES.map(|(code, _)| code).join(", ");
+        eprintln!("unknown language {language:?}, expected one of {known}");
+        std::process::exit(1);
+    });
+    match args.first().map(String::as_str) {
+        Some("stream") => {
+            let path = args.get(1).expect(
+                "usage: day1 stream <path|-> [--digits] [--language <code>] [--ignore-case]",
+            );
+            let scanner = if args.iter().any(|arg| arg == "--digits") {
+                Scanner::digits()
+            } else {
+                Scanner::spelled(words, ignore_case)
+            };
+            stream(&scanner, path);
+        }
+        _ if args.iter().any(|arg| arg == "--explain") => {
+            let disagreements_only = args.iter().any(|arg| arg == "--disagreements");
+            let scanner = Scanner::spelled(words, ignore_case);
+            print!("{}", explain(input, &scanner, disagreements_only));
+        }
+        _ => {
+            report("1", part1(input));
+            if language.is_none() && !ignore_case {
+                report("2", part2(input));
+            } else {
+                let case = if ignore_case { ", ignoring case" } else { "" };
+                let label = format!("2 ({}{})", language.unwrap_or("en"), case);
+                let scanner = Scanner::spelled(words, ignore_case);
+                report(&label, calibration_sum(&scanner, input));
+            }
+        }
+    }
 }
 
-fn part1(input: &str) -> i32 {
-    input
-        .lines()
-        .map(|line| {
-            let first = line.chars().find(|c| c.is_digit(10)).expect("first digit");
-            let last = line
-                .chars()
-                .rev()
-                .find(|c| c.is_digit(10))
-                .expect("last digit");
-            return format!("{}{}", first, last).parse::<i32>().expect("parse");
-        })
-        .sum()
-}
-
-fn part2(input: &str) -> i32 {
-    let numbers = [
-        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
-    ];
-    return input
-        .lines()
-        .map(|line| -> i32 {
-            let mut first = None;
-            'char_loop: for i in 0..line.len() {
-                let char = line.as_bytes()[i] as char;
-                if char.is_digit(10) {
-                    first = Some(char);
-                    break;
-                }
-                for (index, spelled) in numbers.iter().enumerate() {
-                    if line[i..].starts_with(spelled) {
-                        first = Some(std::char::from_digit((index + 1) as u32, 10).unwrap());
-                        break 'char_loop;
-                    }
+const DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+/// Spelled digits one to nine, by language code.
+const VOCABULARIES: [(&str, [&str; 9]); 4] = [
+    (
+        "en",
+        [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ],
+    ),
+    (
+        "pt",
+        [
+            "um", "dois", "três", "quatro", "cinco", "seis", "sete", "oito", "nove",
+        ],
+    ),
+    (
+        "de",
+        [
+            "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+        ],
+    ),
+    (
+        "es",
+        [
+            "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+        ],
+    ),
+];
+
+fn vocabulary(language: &str) -> Option<&'static [&'static str; 9]> {
+    return VOCABULARIES
+        .iter()
+        .find(|(code, _)| *code == language)
+        .map(|(_, words)| words);
+}
+
+/// Longest pattern, in chars after case folding, a scanner accepts; bounds
+/// how far back `Matches` remembers where chars started.
+const MAX_PATTERN_CHARS: usize = 32;
+
+/// Aho-Corasick automaton finding every occurrence of a set of patterns,
+/// overlapping ones included, in a single pass over the text.
+struct Scanner {
+    /// Next state for every state and byte, with failure links folded in.
+    transitions: Vec<[u32; 256]>,
+    /// Patterns ending at each state, as indices into `patterns`.
+    outputs: Vec<Vec<usize>>,
+    /// Every pattern with the digit it stands for, lowercased when folding
+    /// case.
+    patterns: Vec<(String, u32)>,
+    /// Whether text is lowercased before matching.
+    fold_case: bool,
+}
+
+impl Scanner {
+    fn new(patterns: &[(&str, u32)], fold_case: bool) -> Self {
+        let patterns = patterns
+            .iter()
+            .map(|(pattern, digit)| {
+                let folded = if fold_case {
+                    fold(pattern)
+                } else {
+                    pattern.to_string()
+                };
+                // Checked after folding, which may lengthen the pattern.
+                assert!(
+                    folded.chars().count() <= MAX_PATTERN_CHARS,
+                    "pattern too long: {pattern}"
+                );
+                (folded, *digit)
+            })
+            .collect::<Vec<_>>();
+        // Build the trie, with 0 meaning "no edge" since the root is never a
+        // child.
+        let mut transitions = vec![[0u32; 256]];
+        let mut outputs = vec![Vec::new()];
+        for (index, (pattern, _)) in patterns.iter().enumerate() {
+            let mut state = 0;
+            for &byte in pattern.as_bytes() {
+                if transitions[state][byte as usize] == 0 {
+                    transitions.push([0; 256]);
+                    outputs.push(Vec::new());
+                    transitions[state][byte as usize] = (transitions.len() - 1) as u32;
                 }
+                state = transitions[state][byte as usize] as usize;
             }
-            let mut last = None;
-            'char_loop: for i in (0..line.len()).rev() {
-                let char = line.as_bytes()[i] as char;
-                if char.is_digit(10) {
-                    last = Some(char);
-                    break;
+            outputs[state].push(index);
+        }
+        // Breadth-first, point every missing edge to where the longest proper
+        // suffix would go, and inherit that suffix's outputs.
+        let mut failure = vec![0usize; transitions.len()];
+        let mut queue = transitions[0]
+            .iter()
+            .filter(|&&next| next != 0)
+            .map(|&next| next as usize)
+            .collect::<VecDeque<_>>();
+        while let Some(state) = queue.pop_front() {
+            let inherited = outputs[failure[state]].clone();
+            outputs[state].extend(inherited);
+            let fallback = transitions[failure[state]];
+            for (byte, next) in transitions[state].iter_mut().enumerate() {
+                if *next == 0 {
+                    *next = fallback[byte];
+                } else {
+                    failure[*next as usize] = fallback[byte] as usize;
+                    queue.push_back(*next as usize);
                 }
-                for (index, spelled) in numbers.iter().enumerate() {
-                    if line[i..].starts_with(spelled) {
-                        last = Some(std::char::from_digit((index + 1) as u32, 10).unwrap());
-                        break 'char_loop;
+            }
+        }
+        return Scanner {
+            transitions,
+            outputs,
+            patterns,
+            fold_case,
+        };
+    }
+
+    /// Matches `0` to `9`.
+    fn digits() -> Self {
+        let patterns = DIGITS
+            .iter()
+            .zip(0..)
+            .map(|(pattern, digit)| (*pattern, digit))
+            .collect::<Vec<_>>();
+        return Scanner::new(&patterns, false);
+    }
+
+    /// Matches `0` to `9` and the given words for one to nine.
+    fn spelled(words: &[&str; 9], fold_case: bool) -> Self {
+        let patterns = DIGITS
+            .iter()
+            .zip(0..)
+            .chain(words.iter().zip(1..))
+            .map(|(pattern, digit)| (*pattern, digit))
+            .collect::<Vec<_>>();
+        return Scanner::new(&patterns, fold_case);
+    }
+
+    fn scan<'a>(&'a self, text: &'a str) -> Matches<'a> {
+        return Matches {
+            scanner: self,
+            text,
+            chars: text.char_indices(),
+            end: 0,
+            folded: ['\0'; 3],
+            folded_count: 0,
+            folded_next: 0,
+            folded_end: 0,
+            starts: [(0, 0); MAX_PATTERN_CHARS],
+            seen: 0,
+            state: 0,
+            pending: 0,
+        };
+    }
+}
+
+/// Lowercases each char on its own, as `Matches` does while scanning.
+fn fold(text: &str) -> String {
+    return text.chars().flat_map(char::to_lowercase).collect();
+}
+
+/// Every `(position, digit, matched_text)` in a text, ordered by where the
+/// match ends. Positions are byte offsets into the original text and always
+/// fall on char boundaries.
+struct Matches<'a> {
+    scanner: &'a Scanner,
+    text: &'a str,
+    chars: std::str::CharIndices<'a>,
+    /// End of the last char taken from `chars`.
+    end: usize,
+    /// That char after case folding, which may expand to several chars.
+    folded: [char; 3],
+    folded_count: usize,
+    /// Folded chars already fed to the automaton.
+    folded_next: usize,
+    /// Length of the folded text fed so far.
+    folded_end: usize,
+    /// `(folded_start, start)` of the most recent chars, as a ring buffer.
+    starts: [(usize, usize); MAX_PATTERN_CHARS],
+    /// Chars taken so far.
+    seen: usize,
+    state: usize,
+    /// Outputs of `state` already yielded.
+    pending: usize,
+}
+
+impl Matches<'_> {
+    /// Maps the start of a match in the folded text back to the original
+    /// text, rounding down when it falls inside a char's expansion.
+    fn original_start(&self, folded_start: usize) -> usize {
+        return (1..=self.seen.min(MAX_PATTERN_CHARS))
+            .map(|back| self.starts[(self.seen - back) % MAX_PATTERN_CHARS])
+            .find(|&(folded, _)| folded <= folded_start)
+            .map(|(_, start)| start)
+            .expect("match within remembered chars");
+    }
+}
+
+impl<'a> Iterator for Matches<'a> {
+    type Item = (usize, u32, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(&pattern) = self.scanner.outputs[self.state].get(self.pending) {
+                self.pending += 1;
+                let (text, digit) = &self.scanner.patterns[pattern];
+                let start = self.original_start(self.folded_end - text.len());
+                return Some((start, *digit, &self.text[start..self.end]));
+            }
+            if self.folded_next == self.folded_count {
+                let (start, c) = self.chars.next()?;
+                self.starts[self.seen % MAX_PATTERN_CHARS] = (self.folded_end, start);
+                self.seen += 1;
+                self.end = start + c.len_utf8();
+                self.folded_count = 0;
+                self.folded_next = 0;
+                if self.scanner.fold_case {
+                    for lower in c.to_lowercase() {
+                        self.folded[self.folded_count] = lower;
+                        self.folded_count += 1;
                     }
+                } else {
+                    self.folded[0] = c;
+                    self.folded_count = 1;
                 }
             }
-            let first = first.expect("first");
-            let last = last.expect("last");
-            return format!("{}{}", first, last).parse::<i32>().expect("parse");
-        })
+            let c = self.folded[self.folded_next];
+            self.folded_next += 1;
+            let mut buffer = [0; 4];
+            for &byte in c.encode_utf8(&mut buffer).as_bytes() {
+                self.state = self.scanner.transitions[self.state][byte as usize] as usize;
+            }
+            self.folded_end += c.len_utf8();
+            self.pending = 0;
+        }
+    }
+}
+
+/// A line without any digit, by one-based line number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct NoDigits {
+    line: usize,
+}
+
+impl Display for NoDigits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {} has no digits", self.line)
+    }
+}
+
+impl std::error::Error for NoDigits {}
+
+/// A `(position, digit, matched_text)` found by a `Scanner`.
+type Match<'a> = (usize, u32, &'a str);
+
+/// The matches starting first and last in a line.
+fn first_and_last<'a>(scanner: &'a Scanner, line: &'a str) -> Option<(Match<'a>, Match<'a>)> {
+    let mut matches = scanner.scan(line);
+    let first = matches.next()?;
+    return Some(matches.fold((first, first), |(first, last), found| {
+        (
+            if found.0 < first.0 { found } else { first },
+            if found.0 > last.0 { found } else { last },
+        )
+    }));
+}
+
+fn calibration_value(scanner: &Scanner, line: &str) -> Option<i32> {
+    let ((_, first, _), (_, last, _)) = first_and_last(scanner, line)?;
+    return Some((first * 10 + last) as i32);
+}
+
+/// Wraps the first and last matches of a line in brackets, as a single span
+/// when they overlap.
+fn highlight(line: &str, first: Match, last: Match) -> String {
+    let first = first.0..first.0 + first.2.len();
+    let last = last.0..last.0 + last.2.len();
+    if last.start < first.end {
+        return format!(
+            "{}[{}]{}",
+            &line[..first.start],
+            &line[first.start..last.end.max(first.end)],
+            &line[last.end.max(first.end)..]
+        );
+    }
+    return format!(
+        "{}[{}]{}[{}]{}",
+        &line[..first.start],
+        &line[first.clone()],
+        &line[first.end..last.start],
+        &line[last.clone()],
+        &line[last.end..]
+    );
+}
+
+/// Per-line trace of both parts, with the digits part 2 picked highlighted.
+/// With `disagreements_only`, lists only lines where the parts differ.
+fn explain(input: &str, spelled: &Scanner, disagreements_only: bool) -> String {
+    let digits = Scanner::digits();
+    let mut table = format!(
+        "{:>5}  {:>6}  {:>6}  {}\n",
+        "Line", "Part 1", "Part 2", "Text"
+    );
+    let (mut shown, mut total) = (0, 0);
+    for (index, line) in input.lines().enumerate() {
+        total += 1;
+        let part1 = calibration_value(&digits, line);
+        let part2 = first_and_last(spelled, line);
+        if disagreements_only && part1 == part2.map(|((_, a, _), (_, b, _))| (a * 10 + b) as i32) {
+            continue;
+        }
+        shown += 1;
+        let show = |value: Option<i32>| value.map_or("-".to_string(), |value| value.to_string());
+        let (value, text) = match part2 {
+            Some((first, last)) => (
+                Some((first.1 * 10 + last.1) as i32),
+                highlight(line, first, last),
+            ),
+            None => (None, line.to_string()),
+        };
+        table.push_str(&format!(
+            "{:>5}  {:>6}  {:>6}  {}\n",
+            index + 1,
+            show(part1),
+            show(value),
+            text
+        ));
+    }
+    table.push_str(&format!("Lines shown: {} of {}\n", shown, total));
+    return table;
+}
+
+fn calibration_sum(scanner: &Scanner, input: &str) -> Result<i32, NoDigits> {
+    return input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| calibration_value(scanner, line).ok_or(NoDigits { line: index + 1 }))
         .sum();
 }
 
+/// Totals of a streamed calibration document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Calibration {
+    total: u64,
+    lines: usize,
+    bytes: u64,
+}
+
+/// Sums calibration values line by line, reusing a single buffer so memory
+/// stays bounded by the longest line. Lines without digits and invalid UTF-8
+/// are reported as `InvalidData`.
+fn calibrate_stream(scanner: &Scanner, mut reader: impl BufRead) -> io::Result<Calibration> {
+    let mut calibration = Calibration::default();
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        let read = reader.read_until(b'\n', &mut buffer)?;
+        if read == 0 {
+            return Ok(calibration);
+        }
+        calibration.lines += 1;
+        calibration.bytes += read as u64;
+        let line = buffer.strip_suffix(b"\n").unwrap_or(&buffer);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let line = std::str::from_utf8(line)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        let value = calibration_value(scanner, line).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                NoDigits {
+                    line: calibration.lines,
+                },
+            )
+        })?;
+        calibration.total += value as u64;
+    }
+}
+
+/// Streams a file, or stdin for `-`, and reports the total with throughput.
+fn stream(scanner: &Scanner, path: &str) {
+    let start = Instant::now();
+    let result = if path == "-" {
+        calibrate_stream(scanner, io::stdin().lock())
+    } else {
+        File::open(path).and_then(|file| calibrate_stream(scanner, BufReader::new(file)))
+    };
+    let elapsed = start.elapsed();
+    match result {
+        Ok(calibration) => {
+            let megabytes = calibration.bytes as f64 / (1024.0 * 1024.0);
+            println!("Total: {}", calibration.total);
+            println!(
+                "{} lines, {:.1} MiB in {:.2?} ({:.1} MiB/s)",
+                calibration.lines,
+                megabytes,
+                elapsed,
+                megabytes / elapsed.as_secs_f64()
+            );
+        }
+        Err(error) => {
+            eprintln!("{}: {}", path, error);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn report(part: &str, result: Result<i32, NoDigits>) {
+    match result {
+        Ok(total) => println!("Part {}: {}", part, total),
+        Err(error) => println!("Part {}: {}", part, error),
+    }
+}
+
+fn part1(input: &str) -> Result<i32, NoDigits> {
+    return calibration_sum(&Scanner::digits(), input);
+}
+
+fn part2(input: &str) -> Result<i32, NoDigits> {
+    let english = vocabulary("en").expect("english vocabulary");
+    return calibration_sum(&Scanner::spelled(english, false), input);
+}
+
 #[cfg(test)]
 mod tests {
     const TEST_INPUT_PART_1: &str = "1abc2\n\
@@ -76,11 +488,175 @@ mod tests {
                                     7pqrstsixteen";
     #[test]
     fn part1() {
-        assert_eq!(super::part1(TEST_INPUT_PART_1), 142);
+        assert_eq!(super::part1(TEST_INPUT_PART_1), Ok(142));
     }
 
     #[test]
     fn part2() {
-        assert_eq!(super::part2(TEST_INPUT_PART_2), 281);
+        assert_eq!(super::part2(TEST_INPUT_PART_2), Ok(281));
+    }
+
+    #[test]
+    fn overlapping_matches() {
+        let english = super::vocabulary("en").unwrap();
+        let scanner = super::Scanner::spelled(english, false);
+        assert_eq!(
+            scanner.scan("xeightwone7").collect::<Vec<_>>(),
+            vec![(1, 8, "eight"), (5, 2, "two"), (7, 1, "one"), (10, 7, "7")]
+        );
+        assert_eq!(
+            scanner.scan("ssseven").collect::<Vec<_>>(),
+            vec![(2, 7, "seven")]
+        );
+        assert_eq!(scanner.scan("abc").count(), 0);
+    }
+
+    #[test]
+    fn contained_patterns() {
+        let scanner = super::Scanner::new(&[("he", 1), ("she", 2), ("hers", 3)], false);
+        assert_eq!(
+            scanner.scan("ushers").collect::<Vec<_>>(),
+            vec![(1, 2, "she"), (2, 1, "he"), (2, 3, "hers")]
+        );
+    }
+
+    #[test]
+    fn localised_vocabularies() {
+        let portuguese = super::vocabulary("pt").unwrap();
+        let scanner = super::Scanner::spelled(portuguese, false);
+        assert_eq!(
+            scanner.scan("umdoistrês").collect::<Vec<_>>(),
+            vec![(0, 1, "um"), (2, 2, "dois"), (6, 3, "três")]
+        );
+        assert_eq!(super::calibration_value(&scanner, "xdoisytrêsz"), Some(23));
+        let german = super::vocabulary("de").unwrap();
+        let scanner = super::Scanner::spelled(german, false);
+        assert_eq!(super::calibration_value(&scanner, "äfünfzweiß"), Some(52));
+        let spanish = super::vocabulary("es").unwrap();
+        let scanner = super::Scanner::spelled(spanish, false);
+        assert_eq!(super::calibration_value(&scanner, "cuatro7nueve"), Some(49));
+        assert!(super::vocabulary("xx").is_none());
+    }
+
+    #[test]
+    fn ignore_case() {
+        let portuguese = super::vocabulary("pt").unwrap();
+        let sensitive = super::Scanner::spelled(portuguese, false);
+        assert_eq!(sensitive.scan("DoisTRÊS").count(), 0);
+        let insensitive = super::Scanner::spelled(portuguese, true);
+        assert_eq!(
+            insensitive.scan("DoisTRÊS").collect::<Vec<_>>(),
+            vec![(0, 2, "Dois"), (4, 3, "TRÊS")]
+        );
+        // 'İ' lowercases to two chars; the match covers the whole original.
+        let dotted = super::Scanner::new(&[("i", 1)], true);
+        assert_eq!(dotted.scan("aİb").collect::<Vec<_>>(), vec![(1, 1, "İ")]);
+        // Sixteen 'İ' fold to exactly the longest pattern allowed.
+        let longest = "İ".repeat(16);
+        let scanner = super::Scanner::new(&[(&longest, 1)], true);
+        let text = format!("x{}", "İ".repeat(20));
+        assert_eq!(
+            scanner
+                .scan(&text)
+                .map(|(start, ..)| start)
+                .collect::<Vec<_>>(),
+            (0..5).map(|offset| 1 + 2 * offset).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "pattern too long")]
+    fn folded_pattern_too_long() {
+        super::Scanner::new(&[(&"İ".repeat(32), 1)], true);
+    }
+
+    #[test]
+    fn non_ascii_lines() {
+        let english = super::vocabulary("en").unwrap();
+        let scanner = super::Scanner::spelled(english, true);
+        let line = "ñ¹one€✓2🎄twoé";
+        let matches = scanner.scan(line).collect::<Vec<_>>();
+        assert_eq!(matches, vec![(4, 1, "one"), (13, 2, "2"), (18, 2, "two")]);
+        assert!(matches
+            .iter()
+            .all(|(position, _, _)| line.is_char_boundary(*position)));
+        assert_eq!(super::calibration_value(&scanner, line), Some(12));
+        assert_eq!(super::calibration_value(&scanner, "ÉÀ€"), None);
+    }
+
+    #[test]
+    fn lines_without_digits() {
+        assert_eq!(
+            super::part1("a1\nno digits\n2b"),
+            Err(super::NoDigits { line: 2 })
+        );
+        assert_eq!(super::part2("é\none"), Err(super::NoDigits { line: 1 }));
+        assert_eq!(
+            super::NoDigits { line: 2 }.to_string(),
+            "line 2 has no digits"
+        );
+    }
+
+    #[test]
+    fn streaming() {
+        let english = super::vocabulary("en").unwrap();
+        let scanner = super::Scanner::spelled(english, false);
+        let input = format!("{TEST_INPUT_PART_2}\n")
+            .replace('\n', "\r\n")
+            .repeat(1000);
+        let calibration =
+            super::calibrate_stream(&scanner, std::io::Cursor::new(input.as_bytes())).unwrap();
+        assert_eq!(
+            calibration,
+            super::Calibration {
+                total: 281_000,
+                lines: 7000,
+                bytes: input.len() as u64,
+            }
+        );
+        // A missing trailing newline still counts the last line.
+        let calibration = super::calibrate_stream(&scanner, "1\n2\nthree".as_bytes()).unwrap();
+        assert_eq!((calibration.total, calibration.lines), (11 + 22 + 33, 3));
+    }
+
+    #[test]
+    fn streaming_errors() {
+        let scanner = super::Scanner::digits();
+        let error = super::calibrate_stream(&scanner, "1\nnope\n".as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "line 2 has no digits");
+        let error = super::calibrate_stream(&scanner, &b"1\n\xff2\n"[..]).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn highlight() {
+        let english = super::vocabulary("en").unwrap();
+        let scanner = super::Scanner::spelled(english, false);
+        let highlighted = |line| {
+            let (first, last) = super::first_and_last(&scanner, line).unwrap();
+            super::highlight(line, first, last)
+        };
+        assert_eq!(highlighted("two1nine"), "[two]1[nine]");
+        assert_eq!(highlighted("treb7uchet"), "treb[7]uchet");
+        assert_eq!(highlighted("xeightwo"), "x[eightwo]");
+        assert_eq!(highlighted("é1ß2ü"), "é[1]ß[2]ü");
+    }
+
+    #[test]
+    fn explain() {
+        let english = super::vocabulary("en").unwrap();
+        let scanner = super::Scanner::spelled(english, false);
+        let table = super::explain(TEST_INPUT_PART_2, &scanner, false);
+        let lines = table.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], " Line  Part 1  Part 2  Text");
+        assert_eq!(lines[1], "    1      11      29  [two]1[nine]");
+        assert_eq!(lines[2], "    2       -      83  [eight]wo[three]");
+        assert_eq!(lines[8], "Lines shown: 7 of 7");
+        let table = super::explain("1abc2\ntwo1nine\n3", &scanner, true);
+        assert_eq!(
+            table,
+            " Line  Part 1  Part 2  Text\n    2      11      29  [two]1[nine]\nLines shown: 1 of 3\n"
+        );
     }
 }
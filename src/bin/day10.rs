@@ -1,11 +1,41 @@
-use petgraph::graphmap::UnGraphMap;
+use std::collections::{HashMap, HashSet};
+
+use petgraph::{
+    dot::{Config, Dot},
+    graphmap::UnGraphMap,
+    visit::Bfs,
+};
 
 fn main() {
     let input = include_str!("../../input/day10.txt");
-    println!("Part 1: {}", part1(input));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let only_loop = args.iter().any(|arg| arg == "--loop");
+    match args.first().map(String::as_str) {
+        Some("dot") => println!("{}", to_dot(&export_graph(input, only_loop))),
+        Some("svg") => println!("{}", to_svg(&export_graph(input, only_loop))),
+        Some("heatmap") => print!(
+            "{}",
+            to_ascii_heatmap(input, &distances(&parse_graph(input)))
+        ),
+        Some("ppm") => print!("{}", to_ppm_heatmap(input, &distances(&parse_graph(input)))),
+        Some("at") => {
+            let distance = args
+                .get(1)
+                .and_then(|distance| distance.parse().ok())
+                .expect("usage: day10 at <distance>");
+            for (c, (row, column)) in tiles_at_distance(&distances(&parse_graph(input)), distance) {
+                println!("{} at ({}, {})", c, row, column);
+            }
+        }
+        _ => println!("Part 1: {}", part1(input)),
+    }
 }
 
-fn parse_graph(input: &str) -> UnGraphMap<(char, (usize, usize)), u64> {
+type Tile = (char, (usize, usize));
+type Graph = UnGraphMap<Tile, u64>;
+type Distances = HashMap<Tile, u64>;
+
+fn parse_graph(input: &str) -> Graph {
     let graph = UnGraphMap::from_edges(input.lines().enumerate().flat_map(|(x, line)| {
         line.chars()
             .enumerate()
@@ -84,14 +114,172 @@ fn parse_graph(input: &str) -> UnGraphMap<(char, (usize, usize)), u64> {
 
 fn part1(input: &str) -> u64 {
     let graph = parse_graph(input);
-    let start = graph.nodes().find(|node| node.0 == 'S').unwrap();
-    let longest_path = petgraph::algo::dijkstra(&graph, start, None, |_| 1u64)
-        .into_values()
-        .max()
-        .unwrap();
+    let longest_path = distances(&graph).into_values().max().unwrap();
     return longest_path;
 }
 
+/// Number of steps from `S` to every tile reachable through the pipes.
+fn distances(graph: &Graph) -> Distances {
+    return petgraph::algo::dijkstra(graph, find_start(graph), None, |_| 1u64);
+}
+
+fn tiles_at_distance(distances: &Distances, distance: u64) -> Vec<Tile> {
+    let mut tiles = distances
+        .iter()
+        .filter_map(|(tile, d)| (*d == distance).then_some(*tile))
+        .collect::<Vec<_>>();
+    tiles.sort_by_key(|(_, position)| *position);
+    return tiles;
+}
+
+/// Lays the distances out on the input grid, `None` for unreachable tiles.
+fn distance_grid(input: &str, distances: &Distances) -> Vec<Vec<Option<u64>>> {
+    let mut grid = input
+        .lines()
+        .map(|line| vec![None; line.chars().count()])
+        .collect::<Vec<_>>();
+    for ((_, (row, column)), distance) in distances {
+        grid[*row][*column] = Some(*distance);
+    }
+    return grid;
+}
+
+const HEATMAP_SHADES: &[char] = &['.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+fn to_ascii_heatmap(input: &str, distances: &Distances) -> String {
+    let max = distances.values().copied().max().unwrap_or(0).max(1);
+    let mut heatmap = String::new();
+    for row in distance_grid(input, distances) {
+        for distance in row {
+            heatmap.push(match distance {
+                Some(distance) => {
+                    HEATMAP_SHADES[(distance * (HEATMAP_SHADES.len() as u64 - 1) / max) as usize]
+                }
+                None => ' ',
+            });
+        }
+        heatmap.push('\n');
+    }
+    return heatmap;
+}
+
+/// Plain (P3) PPM image, one pixel per tile, going from blue at `S` to red
+/// at the farthest tile. Unreachable tiles are black.
+fn to_ppm_heatmap(input: &str, distances: &Distances) -> String {
+    let max = distances.values().copied().max().unwrap_or(0).max(1);
+    let grid = distance_grid(input, distances);
+    let width = grid.first().map_or(0, Vec::len);
+    let mut ppm = format!("P3\n{} {}\n255\n", width, grid.len());
+    for row in grid {
+        let pixels = row
+            .into_iter()
+            .map(|distance| match distance {
+                Some(distance) => {
+                    let red = distance * 255 / max;
+                    format!("{} 0 {}", red, 255 - red)
+                }
+                None => "0 0 0".to_string(),
+            })
+            .collect::<Vec<_>>();
+        ppm.push_str(&pixels.join(" "));
+        ppm.push('\n');
+    }
+    return ppm;
+}
+
+fn find_start(graph: &Graph) -> Tile {
+    return graph.nodes().find(|node| node.0 == 'S').unwrap();
+}
+
+/// Keeps only the tiles connected to `S` and the pipes between them.
+fn main_loop(graph: &Graph) -> Graph {
+    let mut visited = HashSet::new();
+    let mut bfs = Bfs::new(graph, find_start(graph));
+    while let Some(node) = bfs.next(graph) {
+        visited.insert(node);
+    }
+    return Graph::from_edges(
+        graph
+            .all_edges()
+            .filter(|(a, b, _)| visited.contains(a) && visited.contains(b))
+            .map(|(a, b, weight)| (a, b, *weight)),
+    );
+}
+
+fn export_graph(input: &str, only_loop: bool) -> Graph {
+    let graph = parse_graph(input);
+    if only_loop {
+        return main_loop(&graph);
+    }
+    return graph;
+}
+
+/// Renders the graph as DOT, pinning every tile to its grid position so
+/// `neato -n` draws the maze the way it looks in the input.
+fn to_dot(graph: &Graph) -> String {
+    let dot = Dot::with_attr_getters(
+        graph,
+        &[Config::NodeNoLabel, Config::EdgeNoLabel],
+        &|_, _| String::new(),
+        &|_, (_, (c, (row, column)))| {
+            let color = if *c == 'S' { ", color = red" } else { "" };
+            format!(
+                "label = \"{}\", pos = \"{},{}!\"{}",
+                c,
+                column,
+                -(*row as i64),
+                color
+            )
+        },
+    );
+    return format!("{:?}", dot);
+}
+
+const SVG_CELL_SIZE: usize = 10;
+
+/// Renders the graph as SVG: every pipe is a line between tile centres and
+/// the start tile is marked with a red dot.
+fn to_svg(graph: &Graph) -> String {
+    let center = |(row, column): (usize, usize)| {
+        (
+            column * SVG_CELL_SIZE + SVG_CELL_SIZE / 2,
+            row * SVG_CELL_SIZE + SVG_CELL_SIZE / 2,
+        )
+    };
+    let (rows, columns) = graph
+        .nodes()
+        .fold((0, 0), |(rows, columns), (_, (row, column))| {
+            (rows.max(row + 1), columns.max(column + 1))
+        });
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+        columns * SVG_CELL_SIZE,
+        rows * SVG_CELL_SIZE
+    );
+    svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n");
+    for ((_, a), (_, b), _) in graph.all_edges() {
+        let (x1, y1) = center(a);
+        let (x2, y2) = center(b);
+        svg.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\"/>\n",
+            x1, y1, x2, y2
+        ));
+    }
+    for (c, position) in graph.nodes() {
+        if c == 'S' {
+            let (x, y) = center(position);
+            svg.push_str(&format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"red\"/>\n",
+                x,
+                y,
+                SVG_CELL_SIZE / 3
+            ));
+        }
+    }
+    svg.push_str("</svg>\n");
+    return svg;
+}
+
 #[cfg(test)]
 mod tests {
     const INPUT: &str = "\
@@ -105,4 +293,48 @@ mod tests {
     fn part1() {
         assert_eq!(super::part1(INPUT), 8);
     }
+
+    #[test]
+    fn distances() {
+        let distances = super::distances(&super::parse_graph(INPUT));
+        assert_eq!(distances.len(), 16);
+        assert_eq!(super::tiles_at_distance(&distances, 8), vec![('7', (2, 4))]);
+        assert_eq!(
+            super::tiles_at_distance(&distances, 1),
+            vec![('J', (2, 1)), ('|', (3, 0))]
+        );
+    }
+
+    #[test]
+    fn heatmap() {
+        let distances = super::distances(&super::parse_graph(INPUT));
+        assert_eq!(
+            super::to_ascii_heatmap(INPUT, &distances),
+            "  +* \n -=# \n.: %@\n:+*#%\n-=   \n"
+        );
+        let ppm = super::to_ppm_heatmap(INPUT, &distances);
+        assert!(ppm.starts_with("P3\n5 5\n255\n0 0 0 0 0 0 "));
+        assert_eq!(ppm.lines().count(), 8);
+    }
+
+    #[test]
+    fn main_loop() {
+        let graph = super::parse_graph("S7F7\nLJLJ");
+        assert_eq!(graph.node_count(), 8);
+        let main_loop = super::main_loop(&graph);
+        assert_eq!(main_loop.node_count(), 4);
+        assert_eq!(main_loop.edge_count(), 4);
+    }
+
+    #[test]
+    fn export() {
+        let graph = super::export_graph(INPUT, true);
+        let dot = super::to_dot(&graph);
+        assert!(dot.contains("label = \"S\", pos = \"0,-2!\", color = red"));
+        let svg = super::to_svg(&graph);
+        assert!(svg
+            .starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"50\" height=\"50\">"));
+        assert_eq!(svg.matches("<line").count(), 16);
+        assert_eq!(svg.matches("<circle").count(), 1);
+    }
 }
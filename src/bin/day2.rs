@@ -1,22 +1,120 @@
-use nom::{bytes::complete::tag, character::complete::digit1, combinator::map_res};
+use std::collections::BTreeMap;
+
+use nom::{
+    bytes::complete::tag,
+    character::complete::{alpha1, digit1},
+    combinator::{all_consuming, map_res},
+};
 
 fn main() {
     let input = include_str!("../../input/day2.txt");
-    println!("Part 1: {}", part1(input));
-    println!("Part 2: {}", part2(input));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let duplicates = if args.iter().any(|arg| arg == "--lenient") {
+        Duplicates::Sum
+    } else {
+        Duplicates::Reject
+    };
+    let games = match parse_games(input, duplicates) {
+        Ok(games) => games,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+    match args.first().map(String::as_str) {
+        Some("check") => return interactive_check(&games),
+        Some("stats") => {
+            let top = args
+                .iter()
+                .position(|arg| arg == "--top")
+                .and_then(|index| args.get(index + 1))
+                .map_or(5, |top| top.parse().unwrap());
+            let statistics = statistics(&games, top, 1000);
+            if args.iter().any(|arg| arg == "--json") {
+                println!("{}", statistics.json());
+            } else {
+                print!("{}", statistics.table());
+            }
+            return;
+        }
+        _ => {}
+    }
+    match args
+        .iter()
+        .position(|arg| arg == "--bag")
+        .and_then(|index| args.get(index + 1))
+    {
+        Some(bag) => println!(
+            "Part 1: {}",
+            possible_games_sum(&games, &parse_bag(bag).unwrap())
+        ),
+        None => println!("Part 1: {}", part1(&games)),
+    }
+    println!("Part 2: {}", part2(&games));
 }
 
-enum Color {
-    Red,
-    Green,
-    Blue,
-}
+/// Colors multiplied together for a game's power.
+const POWER_COLORS: [&str; 3] = ["red", "green", "blue"];
 
-#[derive(PartialEq, Debug)]
+/// Cube counts by color; colors not present count as zero.
+#[derive(PartialEq, Debug, Default, Clone)]
 struct Cubes {
-    red: i32,
-    green: i32,
-    blue: i32,
+    counts: BTreeMap<String, i32>,
+}
+
+impl Cubes {
+    /// 12 red, 13 green and 14 blue cubes.
+    fn standard_bag() -> Self {
+        return Cubes::from([("red", 12), ("green", 13), ("blue", 14)]);
+    }
+
+    fn count(&self, color: &str) -> i32 {
+        return self.counts.get(color).copied().unwrap_or(0);
+    }
+
+    /// Whether every color fits within the same color in `bag`.
+    fn fits_in(&self, bag: &Cubes) -> bool {
+        return self
+            .counts
+            .iter()
+            .all(|(color, &count)| count <= bag.count(color));
+    }
+
+    /// Raises every count to at least the one in `other`.
+    fn max_with(&mut self, other: &Cubes) {
+        for (color, &count) in &other.counts {
+            let entry = self.counts.entry(color.clone()).or_insert(0);
+            *entry = (*entry).max(count);
+        }
+    }
+
+    fn power(&self, colors: &[&str]) -> i32 {
+        return colors.iter().map(|color| self.count(color)).product();
+    }
+}
+
+impl<const N: usize> From<[(&str, i32); N]> for Cubes {
+    fn from(counts: [(&str, i32); N]) -> Self {
+        return Cubes {
+            counts: counts
+                .into_iter()
+                .map(|(color, count)| (color.to_string(), count))
+                .collect(),
+        };
+    }
+}
+
+/// Lists counts in color order, such as `3 blue, 4 red`.
+impl std::fmt::Display for Cubes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (color, count)) in self.counts.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} {}", count, color)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(PartialEq, Debug)]
@@ -25,96 +123,397 @@ struct Game {
     sets: Vec<Cubes>,
 }
 
-fn parse_cubes(input: &str) -> nom::IResult<&str, Cubes> {
-    let mut cubes = Cubes {
-        red: 0,
-        green: 0,
-        blue: 0,
-    };
-    let (input, _) = nom::multi::separated_list1(tag(", "), |color| {
+/// Canonical form of a game line. `Game::try_from` parses it back as long as
+/// the id and every count are non-negative, the game has at least one set, no
+/// set is empty and every color is a single word of ASCII letters.
+impl std::fmt::Display for Game {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Game {}: ", self.id)?;
+        for (index, set) in self.sets.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", set)?;
+        }
+        Ok(())
+    }
+}
+
+impl Game {
+    /// Fewest cubes of each color that make every set possible.
+    fn minimal_bag(&self) -> Cubes {
+        let mut bag = Cubes::default();
+        for set in &self.sets {
+            bag.max_with(set);
+        }
+        return bag;
+    }
+
+    /// Every color in every set drawing more cubes than `bag` holds.
+    fn violations(&self, bag: &Cubes) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for (index, set) in self.sets.iter().enumerate() {
+            for (color, &count) in &set.counts {
+                let limit = bag.count(color);
+                if count > limit {
+                    violations.push(Violation {
+                        set: index + 1,
+                        color: color.clone(),
+                        count,
+                        limit,
+                    });
+                }
+            }
+        }
+        return violations;
+    }
+
+    fn is_feasible(&self, bag: &Cubes) -> bool {
+        return self.sets.iter().all(|set| set.fits_in(bag));
+    }
+}
+
+/// A color drawn `count` times in a set while the bag only holds `limit`.
+#[derive(PartialEq, Debug)]
+struct Violation {
+    /// One-based.
+    set: usize,
+    color: String,
+    count: i32,
+    limit: i32,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "set {} draws {} {}, bag holds {}",
+            self.set, self.count, self.color, self.limit
+        )
+    }
+}
+
+/// What to do when a color appears more than once in the same set.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Duplicates {
+    Reject,
+    Sum,
+}
+
+#[derive(PartialEq, Debug)]
+enum GameError {
+    Syntax(String),
+    /// `set` is one-based.
+    DuplicateColor {
+        game: i32,
+        set: usize,
+        color: String,
+    },
+}
+
+impl std::fmt::Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameError::Syntax(line) => write!(f, "invalid game {:?}", line),
+            GameError::DuplicateColor { game, set, color } => write!(
+                f,
+                "game {}, set {}: {} appears more than once",
+                game, set, color
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+/// The `(count, color)` pairs of one set, as written.
+type RawSet<'a> = Vec<(i32, &'a str)>;
+
+fn parse_cubes(input: &str) -> nom::IResult<&str, RawSet<'_>> {
+    return nom::multi::separated_list1(tag(", "), |color| {
         let (input, count) = map_res(digit1, str::parse::<i32>)(color)?;
         let (input, _) = tag(" ")(input)?;
-        let (input, color) = nom::branch::alt((
-            map_res(tag("red"), |_| {
-                Ok::<Color, nom::Err<nom::error::Error<&str>>>(Color::Red)
-            }),
-            map_res(tag("green"), |_| {
-                Ok::<Color, nom::Err<nom::error::Error<&str>>>(Color::Green)
-            }),
-            map_res(tag("blue"), |_| {
-                Ok::<Color, nom::Err<nom::error::Error<&str>>>(Color::Blue)
-            }),
-        ))(input)?;
-        match color {
-            Color::Red => cubes.red = count,
-            Color::Green => cubes.green = count,
-            Color::Blue => cubes.blue = count,
-        }
-        Ok((input, ()))
-    })(input)?;
-    Ok((input, cubes))
-}
-
-impl<'a> TryFrom<&'a str> for Game {
-    type Error = nom::Err<nom::error::Error<&'a str>>;
-    fn try_from(input: &'a str) -> anyhow::Result<Self, Self::Error> {
-        let (input, _) = tag("Game ")(input)?;
-        let (input, id) = map_res(digit1, str::parse::<i32>)(input)?;
-        let (input, _) = tag(": ")(input)?;
-        let (_, sets) = nom::multi::separated_list1(tag("; "), parse_cubes)(input)?;
-        Ok(Game { id, sets })
-    }
-}
-
-fn part1(input: &str) -> i32 {
-    let games = input
+        let (input, color) = alpha1(input)?;
+        Ok((input, (count, color)))
+    })(input);
+}
+
+fn parse_sets(input: &str) -> nom::IResult<&str, (i32, Vec<RawSet<'_>>)> {
+    let (input, _) = tag("Game ")(input)?;
+    let (input, id) = map_res(digit1, str::parse::<i32>)(input)?;
+    let (input, _) = tag(": ")(input)?;
+    let (input, sets) = nom::multi::separated_list1(tag("; "), parse_cubes)(input)?;
+    Ok((input, (id, sets)))
+}
+
+fn parse_game(input: &str, duplicates: Duplicates) -> Result<Game, GameError> {
+    let (_, (id, raw_sets)) =
+        all_consuming(parse_sets)(input).map_err(|_| GameError::Syntax(input.to_string()))?;
+    let mut sets = Vec::with_capacity(raw_sets.len());
+    for (index, raw_set) in raw_sets.into_iter().enumerate() {
+        let mut cubes = Cubes::default();
+        for (count, color) in raw_set {
+            match cubes.counts.get_mut(color) {
+                None => {
+                    cubes.counts.insert(color.to_string(), count);
+                }
+                Some(total) if duplicates == Duplicates::Sum => *total += count,
+                Some(_) => {
+                    return Err(GameError::DuplicateColor {
+                        game: id,
+                        set: index + 1,
+                        color: color.to_string(),
+                    })
+                }
+            }
+        }
+        sets.push(cubes);
+    }
+    return Ok(Game { id, sets });
+}
+
+impl TryFrom<&str> for Game {
+    type Error = GameError;
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        return parse_game(input, Duplicates::Reject);
+    }
+}
+
+fn parse_games(input: &str, duplicates: Duplicates) -> Result<Vec<Game>, GameError> {
+    return input
         .lines()
-        .map(Game::try_from)
-        .collect::<Result<Vec<_>, _>>()
-        .unwrap();
-    let possibility_rule = Cubes {
-        red: 12,
-        green: 13,
-        blue: 14,
-    };
-    let mut total = 0;
+        .map(|line| parse_game(line, duplicates))
+        .collect();
+}
+
+/// Parses a color to limit table such as `red=12,green=13,blue=14`.
+fn parse_bag(input: &str) -> anyhow::Result<Cubes> {
+    let mut bag = Cubes::default();
+    for limit in input.split(',') {
+        let (color, count) = limit
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("expected color=limit, got {:?}", limit))?;
+        bag.counts
+            .insert(color.trim().to_string(), count.trim().parse()?);
+    }
+    return Ok(bag);
+}
+
+/// Formats a bag the way `parse_bag` reads it.
+fn format_bag(bag: &Cubes) -> String {
+    return bag
+        .counts
+        .iter()
+        .map(|(color, count)| format!("{}={}", color, count))
+        .collect::<Vec<_>>()
+        .join(",");
+}
+
+fn feasible_games<'a>(games: &'a [Game], bag: &'a Cubes) -> impl Iterator<Item = &'a Game> {
+    return games.iter().filter(|game| game.is_feasible(bag));
+}
+
+/// Smallest bag with which every game is feasible.
+fn minimal_bag(games: &[Game]) -> Cubes {
+    let mut bag = Cubes::default();
+    for game in games {
+        bag.max_with(&game.minimal_bag());
+    }
+    return bag;
+}
+
+/// Sum of the ids of games possible with the cubes in `bag`.
+fn possible_games_sum(games: &[Game], bag: &Cubes) -> i32 {
+    return feasible_games(games, bag).map(|game| game.id).sum();
+}
+
+/// Which games `bag` makes feasible, and why the others are not.
+fn check(games: &[Game], bag: &Cubes) -> String {
+    let feasible = feasible_games(games, bag)
+        .map(|game| game.id.to_string())
+        .collect::<Vec<_>>();
+    let mut report = format!(
+        "Feasible: {} of {} games (id sum {})\n",
+        feasible.len(),
+        games.len(),
+        possible_games_sum(games, bag)
+    );
+    if !feasible.is_empty() {
+        report.push_str(&format!("  {}\n", feasible.join(", ")));
+    }
     for game in games {
-        let valid = game.sets.iter().all(|set| {
-            set.red <= possibility_rule.red
-                && set.green <= possibility_rule.green
-                && set.blue <= possibility_rule.blue
-        });
-        if valid {
-            total += game.id;
+        for violation in game.violations(bag) {
+            report.push_str(&format!("Game {}: {}\n", game.id, violation));
         }
     }
-    return total;
+    return report;
 }
-fn part2(input: &str) -> i32 {
-    let games = input
-        .lines()
-        .map(Game::try_from)
-        .collect::<Result<Vec<_>, _>>()
-        .unwrap();
-    let mut total = 0;
+
+/// Summary of a list of games, built by `statistics`.
+#[derive(PartialEq, Debug)]
+struct Statistics {
+    games: usize,
+    /// Sets drawn per game, on average.
+    average_draws: f64,
+    /// For each color, how many games have each count as their maximum.
+    maxima: BTreeMap<String, BTreeMap<i32, usize>>,
+    /// Width of the power histogram buckets.
+    bucket_width: i32,
+    /// Games per power bucket, keyed by the bucket's lowest power.
+    powers: BTreeMap<i32, usize>,
+    /// `(id, power)` of the most powerful games, strongest first.
+    top: Vec<(i32, i32)>,
+}
+
+fn statistics(games: &[Game], top: usize, bucket_width: i32) -> Statistics {
+    let mut maxima = BTreeMap::<String, BTreeMap<i32, usize>>::new();
+    let mut powers = BTreeMap::new();
+    let mut ranked = Vec::with_capacity(games.len());
+    let mut draws = 0;
     for game in games {
-        let mut power = Cubes {
-            red: 0,
-            green: 0,
-            blue: 0,
-        };
-        for set in game.sets {
-            power.red = power.red.max(set.red);
-            power.green = power.green.max(set.green);
-            power.blue = power.blue.max(set.blue);
+        let bag = game.minimal_bag();
+        for (color, &count) in &bag.counts {
+            *maxima
+                .entry(color.clone())
+                .or_default()
+                .entry(count)
+                .or_insert(0) += 1;
+        }
+        let power = bag.power(&POWER_COLORS);
+        *powers
+            .entry(power / bucket_width * bucket_width)
+            .or_insert(0) += 1;
+        ranked.push((game.id, power));
+        draws += game.sets.len();
+    }
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    ranked.truncate(top);
+    return Statistics {
+        games: games.len(),
+        average_draws: if games.is_empty() {
+            0.0
+        } else {
+            draws as f64 / games.len() as f64
+        },
+        maxima,
+        bucket_width,
+        powers,
+        top: ranked,
+    };
+}
+
+impl Statistics {
+    fn table(&self) -> String {
+        let mut table = format!(
+            "Games: {}\nAverage draws per game: {:.2}\n\nMaximum",
+            self.games, self.average_draws
+        );
+        for color in self.maxima.keys() {
+            table.push_str(&format!("  {:>7}", color));
+        }
+        table.push('\n');
+        let counts = self
+            .maxima
+            .values()
+            .flat_map(|distribution| distribution.keys())
+            .collect::<std::collections::BTreeSet<_>>();
+        for count in counts {
+            table.push_str(&format!("{:>7}", count));
+            for distribution in self.maxima.values() {
+                let games = distribution.get(count).copied().unwrap_or(0);
+                table.push_str(&format!("  {:>7}", games));
+            }
+            table.push('\n');
+        }
+        table.push_str(&format!("\n{:>13}  {:>5}\n", "Power", "Games"));
+        for (&from, &games) in &self.powers {
+            let range = format!("{}-{}", from, from + self.bucket_width - 1);
+            table.push_str(&format!(
+                "{:>13}  {:>5}  {}\n",
+                range,
+                games,
+                "#".repeat(games)
+            ));
+        }
+        table.push_str(&format!("\n{:>4}  {:>4}  {:>6}\n", "Rank", "Game", "Power"));
+        for (index, (id, power)) in self.top.iter().enumerate() {
+            table.push_str(&format!("{:>4}  {:>4}  {:>6}\n", index + 1, id, power));
+        }
+        return table;
+    }
+
+    fn json(&self) -> String {
+        let maxima = self
+            .maxima
+            .iter()
+            .map(|(color, distribution)| {
+                let counts = distribution
+                    .iter()
+                    .map(|(count, games)| format!("\"{}\":{}", count, games))
+                    .collect::<Vec<_>>();
+                format!("\"{}\":{{{}}}", color, counts.join(","))
+            })
+            .collect::<Vec<_>>();
+        let powers = self
+            .powers
+            .iter()
+            .map(|(from, games)| {
+                format!(
+                    "{{\"from\":{},\"to\":{},\"games\":{}}}",
+                    from,
+                    from + self.bucket_width - 1,
+                    games
+                )
+            })
+            .collect::<Vec<_>>();
+        let top = self
+            .top
+            .iter()
+            .map(|(id, power)| format!("{{\"id\":{},\"power\":{}}}", id, power))
+            .collect::<Vec<_>>();
+        return format!(
+            "{{\"games\":{},\"average_draws\":{},\"maxima\":{{{}}},\"powers\":[{}],\"top\":[{}]}}",
+            self.games,
+            self.average_draws,
+            maxima.join(","),
+            powers.join(","),
+            top.join(",")
+        );
+    }
+}
+
+/// Reads bags from stdin, one per line, and checks each against the games.
+fn interactive_check(games: &[Game]) {
+    println!("Minimal bag: {}", format_bag(&minimal_bag(games)));
+    println!("Enter a bag such as red=12,green=13,blue=14, or an empty line to quit.");
+    for line in std::io::stdin().lines() {
+        let line = line.unwrap();
+        if line.trim().is_empty() {
+            break;
+        }
+        match parse_bag(&line) {
+            Ok(bag) => print!("{}", check(games, &bag)),
+            Err(error) => println!("error: {}", error),
         }
-        total += power.red * power.green * power.blue;
+    }
+}
+
+fn part1(games: &[Game]) -> i32 {
+    return possible_games_sum(games, &Cubes::standard_bag());
+}
+
+fn part2(games: &[Game]) -> i32 {
+    let mut total = 0;
+    for game in games {
+        total += game.minimal_bag().power(&POWER_COLORS);
     }
     return total;
 }
 
 #[cfg(test)]
 mod tests {
+    use aoc_2023_rust::random::XorShift;
+
     use super::*;
 
     const INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\n\
@@ -125,7 +524,7 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(INPUT), 8);
+        assert_eq!(part1(&parse_games(INPUT, Duplicates::Reject).unwrap()), 8);
     }
 
     #[test]
@@ -135,21 +534,9 @@ mod tests {
             Ok(Game {
                 id: 1,
                 sets: vec![
-                    Cubes {
-                        red: 4,
-                        green: 0,
-                        blue: 3,
-                    },
-                    Cubes {
-                        red: 1,
-                        green: 2,
-                        blue: 6,
-                    },
-                    Cubes {
-                        red: 0,
-                        green: 2,
-                        blue: 0,
-                    },
+                    Cubes::from([("red", 4), ("blue", 3)]),
+                    Cubes::from([("red", 1), ("green", 2), ("blue", 6)]),
+                    Cubes::from([("green", 2)]),
                 ],
             })
         );
@@ -157,6 +544,220 @@ mod tests {
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(INPUT), 2286);
+        assert_eq!(
+            part2(&parse_games(INPUT, Duplicates::Reject).unwrap()),
+            2286
+        );
+    }
+
+    #[test]
+    fn arbitrary_colors() {
+        let input = "Game 1: 2 purple, 1 red; 3 teal\n\
+                    Game 2: 5 purple\n\
+                    Game 3: 1 orange";
+        let game = Game::try_from("Game 1: 2 purple, 1 red; 3 teal").unwrap();
+        assert_eq!(
+            game.minimal_bag(),
+            Cubes::from([("purple", 2), ("red", 1), ("teal", 3)])
+        );
+        let bag = parse_bag("purple=4, teal=3,red=1").unwrap();
+        let games = parse_games(input, Duplicates::Reject).unwrap();
+        assert_eq!(possible_games_sum(&games, &bag), 1);
+        assert_eq!(possible_games_sum(&games, &Cubes::standard_bag()), 0);
+        assert!(parse_bag("red:12").is_err());
+        assert!(parse_bag("red=many").is_err());
+    }
+
+    #[test]
+    fn duplicate_colors() {
+        let line = "Game 7: 1 blue; 3 red, 2 green, 4 red";
+        let error = Game::try_from(line).unwrap_err();
+        assert_eq!(
+            error,
+            GameError::DuplicateColor {
+                game: 7,
+                set: 2,
+                color: "red".to_string(),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "game 7, set 2: red appears more than once"
+        );
+        assert_eq!(
+            parse_game(line, Duplicates::Sum).unwrap().sets[1],
+            Cubes::from([("red", 7), ("green", 2)])
+        );
+        for line in [
+            "Game x: 1 red",
+            "Game 1: 3 red,4 red",
+            "Game 1: 3 red;4 blue",
+            "Game 1: 3 red; 4 blue junk",
+        ] {
+            assert_eq!(
+                Game::try_from(line),
+                Err(GameError::Syntax(line.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn feasibility_queries() {
+        let games = parse_games(INPUT, Duplicates::Reject).unwrap();
+        let bag = Cubes::standard_bag();
+        assert_eq!(
+            feasible_games(&games, &bag)
+                .map(|game| game.id)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 5]
+        );
+        let minimal = minimal_bag(&games);
+        assert_eq!(
+            minimal,
+            Cubes::from([("red", 20), ("green", 13), ("blue", 15)])
+        );
+        assert_eq!(feasible_games(&games, &minimal).count(), games.len());
+        assert_eq!(format_bag(&minimal), "blue=15,green=13,red=20");
+        assert_eq!(parse_bag(&format_bag(&minimal)).unwrap(), minimal);
+        assert_eq!(
+            games[3].violations(&bag),
+            vec![
+                Violation {
+                    set: 3,
+                    color: "blue".to_string(),
+                    count: 15,
+                    limit: 14,
+                },
+                Violation {
+                    set: 3,
+                    color: "red".to_string(),
+                    count: 14,
+                    limit: 12,
+                },
+            ]
+        );
+        assert_eq!(
+            check(&games, &bag),
+            "Feasible: 3 of 5 games (id sum 8)\n  1, 2, 5\n\
+             Game 3: set 1 draws 20 red, bag holds 12\n\
+             Game 4: set 3 draws 15 blue, bag holds 14\n\
+             Game 4: set 3 draws 14 red, bag holds 12\n"
+        );
+    }
+
+    #[test]
+    fn game_statistics() {
+        let games = parse_games(INPUT, Duplicates::Reject).unwrap();
+        let statistics = statistics(&games, 3, 1000);
+        assert_eq!(statistics.games, 5);
+        assert_eq!(statistics.average_draws, 2.8);
+        assert_eq!(
+            statistics.maxima["green"],
+            BTreeMap::from([(2, 1), (3, 3), (13, 1)])
+        );
+        assert_eq!(statistics.powers, BTreeMap::from([(0, 4), (1000, 1)]));
+        assert_eq!(statistics.top, vec![(3, 1560), (4, 630), (1, 48)]);
+        let table = statistics.table();
+        let lines = table.lines().collect::<Vec<_>>();
+        assert_eq!(lines[3], "Maximum     blue    green      red");
+        assert_eq!(lines[4], "      1        0        0        1");
+        assert_eq!(lines[5], "      2        1        1        0");
+        assert_eq!(lines[14], "        Power  Games");
+        assert_eq!(lines[15], "        0-999      4  ####");
+        assert_eq!(lines[19], "   1     3    1560");
+        assert_eq!(
+            statistics.json(),
+            "{\"games\":5,\"average_draws\":2.8,\"maxima\":{\
+             \"blue\":{\"2\":1,\"4\":1,\"6\":2,\"15\":1},\
+             \"green\":{\"2\":1,\"3\":3,\"13\":1},\
+             \"red\":{\"1\":1,\"4\":1,\"6\":1,\"14\":1,\"20\":1}},\
+             \"powers\":[{\"from\":0,\"to\":999,\"games\":4},{\"from\":1000,\"to\":1999,\"games\":1}],\
+             \"top\":[{\"id\":3,\"power\":1560},{\"id\":4,\"power\":630},{\"id\":1,\"power\":48}]}"
+        );
+    }
+
+    #[test]
+    fn display() {
+        let game =
+            Game::try_from("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+        assert_eq!(
+            game.to_string(),
+            "Game 1: 3 blue, 4 red; 6 blue, 2 green, 1 red; 2 green"
+        );
+        for line in INPUT.lines() {
+            let game = Game::try_from(line).unwrap();
+            assert_eq!(Game::try_from(game.to_string().as_str()), Ok(game));
+        }
+    }
+
+    #[test]
+    fn unprintable_games() {
+        let games = [
+            Game {
+                id: 1,
+                sets: vec![],
+            },
+            Game {
+                id: 2,
+                sets: vec![Cubes::from([("red", 1)]), Cubes::default()],
+            },
+            Game {
+                id: 3,
+                sets: vec![Cubes::from([("red", -1)])],
+            },
+            Game {
+                id: -4,
+                sets: vec![Cubes::from([("red", 1)])],
+            },
+            Game {
+                id: 5,
+                sets: vec![Cubes::from([("dark blue", 1)])],
+            },
+            Game {
+                id: 6,
+                sets: vec![Cubes::from([("azul-escuro", 1)])],
+            },
+        ];
+        let printed = games.map(|game| game.to_string());
+        assert_eq!(
+            printed,
+            [
+                "Game 1: ",
+                "Game 2: 1 red; ",
+                "Game 3: -1 red",
+                "Game -4: 1 red",
+                "Game 5: 1 dark blue",
+                "Game 6: 1 azul-escuro",
+            ]
+        );
+        for line in printed {
+            assert!(Game::try_from(line.as_str()).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn random_round_trip() {
+        const COLORS: [&str; 6] = ["red", "green", "blue", "teal", "purple", "x"];
+        let mut random = XorShift::default();
+        let mut random = |bound: u64| random.below(bound);
+        for _ in 0..1000 {
+            let id = random(10_000) as i32;
+            let sets = (0..1 + random(6))
+                .map(|_| {
+                    let mut set = Cubes::default();
+                    while set.counts.is_empty() {
+                        for color in COLORS {
+                            if random(2) == 0 {
+                                set.counts.insert(color.to_string(), random(100) as i32);
+                            }
+                        }
+                    }
+                    set
+                })
+                .collect();
+            let game = Game { id, sets };
+            let printed = game.to_string();
+            assert_eq!(Game::try_from(printed.as_str()), Ok(game), "{}", printed);
+        }
     }
 }
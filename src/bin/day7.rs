@@ -1,14 +1,54 @@
-use std::{cmp::Ordering, collections::HashMap};
+use std::{cmp::Ordering, fmt::Display, hint::black_box, time::Instant};
+
+use aoc_2023_rust::random::XorShift;
 
 fn main() {
     let input = include_str!("../../input/day7.txt");
-    println!("Part 1: {}", part1(input));
-    println!("Part 2: {}", part2(input));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let flag = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+            .map(String::as_str)
+    };
+    let rules = || {
+        let wildcards = flag("--wild")
+            .unwrap_or("")
+            .chars()
+            .map(parse_card)
+            .collect::<Vec<_>>();
+        let tie_break = match flag("--tie-break") {
+            Some("grouped") => TieBreak::Grouped,
+            _ => TieBreak::Positional,
+        };
+        Rules::new(&wildcards, tie_break)
+    };
+    match args.first().map(String::as_str) {
+        Some("custom") => match total_winnings(input, &rules()) {
+            Ok(total) => println!("Total winnings: {}", total),
+            Err(error) => println!("error: {}", error),
+        },
+        Some("explain") => match explain(input, &rules()) {
+            Ok(table) => print!("{}", table),
+            Err(error) => println!("error: {}", error),
+        },
+        Some("bench") => bench(),
+        _ => {
+            report(1, part1(input));
+            report(2, part2(input));
+        }
+    }
+}
+
+fn report(part: u8, result: Result<u64, MixedHandSizes>) {
+    match result {
+        Ok(total) => println!("Part {}: {}", part, total),
+        Err(error) => println!("Part {}: {}", part, error),
+    }
 }
 
 #[derive(PartialEq, PartialOrd, Clone, Copy, Eq, Hash, Ord, Debug)]
 enum Card {
-    Joker,
     Two,
     Three,
     Four,
@@ -24,26 +64,46 @@ enum Card {
     Ace,
 }
 
-fn parse_day1_card(c: char) -> Card {
-    match c {
-        '2' => Card::Two,
-        '3' => Card::Three,
-        '4' => Card::Four,
-        '5' => Card::Five,
-        '6' => Card::Six,
-        '7' => Card::Seven,
-        '8' => Card::Eight,
-        '9' => Card::Nine,
-        'T' => Card::Ten,
-        'J' => Card::Jack,
-        'Q' => Card::Queen,
-        'K' => Card::King,
-        'A' => Card::Ace,
-        _ => panic!("Invalid card"),
+impl Card {
+    const ALL: [Card; 13] = [
+        Card::Two,
+        Card::Three,
+        Card::Four,
+        Card::Five,
+        Card::Six,
+        Card::Seven,
+        Card::Eight,
+        Card::Nine,
+        Card::Ten,
+        Card::Jack,
+        Card::Queen,
+        Card::King,
+        Card::Ace,
+    ];
+}
+
+impl Display for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            Card::Two => '2',
+            Card::Three => '3',
+            Card::Four => '4',
+            Card::Five => '5',
+            Card::Six => '6',
+            Card::Seven => '7',
+            Card::Eight => '8',
+            Card::Nine => '9',
+            Card::Ten => 'T',
+            Card::Jack => 'J',
+            Card::Queen => 'Q',
+            Card::King => 'K',
+            Card::Ace => 'A',
+        };
+        write!(f, "{}", label)
     }
 }
 
-fn parse_day2_card(c: char) -> Card {
+fn parse_card(c: char) -> Card {
     match c {
         '2' => Card::Two,
         '3' => Card::Three,
@@ -54,182 +114,363 @@ fn parse_day2_card(c: char) -> Card {
         '8' => Card::Eight,
         '9' => Card::Nine,
         'T' => Card::Ten,
+        'J' => Card::Jack,
         'Q' => Card::Queen,
         'K' => Card::King,
         'A' => Card::Ace,
-        'J' => Card::Joker,
         _ => panic!("Invalid card"),
     }
 }
 
-#[derive(PartialEq, Clone, Copy, Eq, Debug)]
-struct Hand(Card, Card, Card, Card, Card);
-
-fn parse_day1_hand(input: &str) -> Hand {
-    let mut chars = input.chars();
-    let card1 = chars.next().expect("Invalid hand");
-    let card2 = chars.next().expect("Invalid hand");
-    let card3 = chars.next().expect("Invalid hand");
-    let card4 = chars.next().expect("Invalid hand");
-    let card5 = chars.next().expect("Invalid hand");
-    Hand(
-        parse_day1_card(card1),
-        parse_day1_card(card2),
-        parse_day1_card(card3),
-        parse_day1_card(card4),
-        parse_day1_card(card5),
-    )
-}
-fn parse_day2_hand(input: &str) -> Hand {
-    let mut chars = input.chars();
-    let card1 = chars.next().expect("Invalid hand");
-    let card2 = chars.next().expect("Invalid hand");
-    let card3 = chars.next().expect("Invalid hand");
-    let card4 = chars.next().expect("Invalid hand");
-    let card5 = chars.next().expect("Invalid hand");
-    Hand(
-        parse_day2_card(card1),
-        parse_day2_card(card2),
-        parse_day2_card(card3),
-        parse_day2_card(card4),
-        parse_day2_card(card5),
-    )
-}
-
-#[derive(PartialEq, PartialOrd, Eq, Ord)]
-enum HandType {
-    HighCard,
-    OnePair,
-    TwoPair,
-    ThreeOfAKind,
-    FullHouse,
-    FourOfAKind,
-    FiveOfAKind,
-}
-
-impl From<Hand> for HandType {
-    fn from(value: Hand) -> Self {
-        let mut seen: HashMap<Card, u64> = HashMap::new();
-        for card in [value.0, value.1, value.2, value.3, value.4] {
-            seen.entry(card)
-                .and_modify(|entry| *entry += 1)
-                .or_insert(1);
+/// Hands can hold any number of cards, as long as every hand in a game holds
+/// the same number. Up to this many cards, hands sort by a packed
+/// [`Rules::sort_key`]; larger ones are compared directly.
+const MAX_PACKED_HAND_SIZE: usize = 15;
+
+#[derive(PartialEq, Clone, Eq, Debug)]
+struct Hand(Vec<Card>);
+
+impl Display for Hand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for card in &self.0 {
+            write!(f, "{}", card)?;
         }
-        match seen {
-            s if s.len() == 1 => HandType::FiveOfAKind,
-            s if s.len() == 2 => {
-                let jokers = s.get(&Card::Joker).unwrap_or(&0);
-                if s.values().any(|&count| count == 4) {
-                    match jokers {
-                        1 | 4 => HandType::FiveOfAKind,
-                        _ => HandType::FourOfAKind,
-                    }
-                } else {
-                    match jokers {
-                        2 | 3 => HandType::FiveOfAKind,
-                        _ => HandType::FullHouse,
-                    }
-                }
-            }
-            s if s.len() == 3 => {
-                let jokers = s.get(&Card::Joker).unwrap_or(&0);
-                if s.values().any(|&count| count == 3) {
-                    match jokers {
-                        1 | 3 => HandType::FourOfAKind,
-                        _ => HandType::ThreeOfAKind,
-                    }
-                } else {
-                    match jokers {
-                        1 => HandType::FullHouse,
-                        2 => HandType::FourOfAKind,
-                        _ => HandType::TwoPair,
-                    }
-                }
-            }
-            s if s.len() == 4 => {
-                let jokers = s.get(&Card::Joker).unwrap_or(&0);
-                match jokers {
-                    1 | 2 => HandType::ThreeOfAKind,
-                    _ => HandType::OnePair,
-                }
+        Ok(())
+    }
+}
+
+fn parse_hand(input: &str) -> Hand {
+    let cards = input.chars().map(parse_card).collect::<Vec<_>>();
+    assert!(!cards.is_empty(), "Invalid hand");
+    Hand(cards)
+}
+
+/// How many copies of each distinct card a hand holds, largest group first.
+/// Between hands of the same size, the lexicographically greater signature
+/// is the stronger type: `[3, 2]` beats `[3, 1, 1]`.
+#[derive(PartialEq, PartialOrd, Eq, Ord, Clone, Debug)]
+struct HandType(Vec<u8>);
+
+/// Names of the five card hand types.
+const NAMES: &[(&[u8], &str)] = &[
+    (&[5], "Five of a kind"),
+    (&[4, 1], "Four of a kind"),
+    (&[3, 2], "Full house"),
+    (&[3, 1, 1], "Three of a kind"),
+    (&[2, 2, 1], "Two pair"),
+    (&[2, 1, 1, 1], "One pair"),
+    (&[1, 1, 1, 1, 1], "High card"),
+];
+
+impl Display for HandType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match NAMES
+            .iter()
+            .find(|(signature, _)| *signature == self.0.as_slice())
+        {
+            Some((_, name)) => write!(f, "{}", name),
+            None => {
+                let groups = self.0.iter().map(u8::to_string).collect::<Vec<_>>();
+                write!(f, "{}", groups.join("+"))
             }
-            s => {
-                let jokers = s.get(&Card::Joker).unwrap_or(&0);
-                match jokers {
-                    1 => HandType::OnePair,
-                    _ => HandType::HighCard,
-                }
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum TieBreak {
+    /// Compare the cards in the order they were dealt, as Camel Cards does.
+    Positional,
+    /// Compare the most repeated cards first, as poker does, so `23332`
+    /// beats `32223`. Wildcards keep their own rank here.
+    Grouped,
+}
+
+/// Everything that differs between Camel Cards variants.
+#[derive(Clone, Debug)]
+struct Rules {
+    /// Strength of each card, indexed by `Card as usize`.
+    strengths: [u8; 13],
+    /// Whether each card, indexed by `Card as usize`, joins the largest group.
+    wildcards: [bool; 13],
+    tie_break: TieBreak,
+}
+
+impl Rules {
+    /// `wildcards` become the weakest cards, in the given order.
+    fn new(wildcards: &[Card], tie_break: TieBreak) -> Self {
+        let ranking = wildcards
+            .iter()
+            .chain(Card::ALL.iter().filter(|card| !wildcards.contains(card)));
+        let mut strengths = [0; 13];
+        for (strength, card) in ranking.enumerate() {
+            strengths[*card as usize] = strength as u8;
+        }
+        return Rules {
+            strengths,
+            wildcards: Card::ALL.map(|card| wildcards.contains(&card)),
+            tie_break,
+        };
+    }
+
+    /// Part 1: `J` is a Jack.
+    fn standard() -> Self {
+        return Rules::new(&[], TieBreak::Positional);
+    }
+
+    /// Part 2: `J` is a Joker.
+    fn jokers() -> Self {
+        return Rules::new(&[Card::Jack], TieBreak::Positional);
+    }
+
+    fn strength(&self, card: Card) -> u8 {
+        return self.strengths[card as usize];
+    }
+
+    /// Count signature of a hand, with the wildcards joining the largest group.
+    fn hand_type(&self, hand: &Hand) -> HandType {
+        let mut counts = [0u8; 13];
+        let mut wildcards = 0;
+        for card in &hand.0 {
+            if self.wildcards[*card as usize] {
+                wildcards += 1;
+            } else {
+                counts[*card as usize] += 1;
             }
         }
+        let mut signature = counts
+            .into_iter()
+            .filter(|&count| count > 0)
+            .collect::<Vec<_>>();
+        signature.sort_unstable_by(|a, b| b.cmp(a));
+        match signature.first_mut() {
+            Some(largest) => *largest += wildcards,
+            None => signature.push(wildcards),
+        }
+        return HandType(signature);
+    }
+
+    /// The hand with every wildcard replaced by the card it stands in for:
+    /// the strongest of the largest groups, or the strongest card overall
+    /// when every card is wild.
+    fn substitution(&self, hand: &Hand) -> Hand {
+        let natural = |card: &&Card| !self.wildcards[**card as usize];
+        let count = |card: Card| hand.0.iter().filter(|&&c| c == card).count();
+        let target = hand
+            .0
+            .iter()
+            .filter(natural)
+            .max_by_key(|&&card| (count(card), self.strength(card)))
+            .or_else(|| {
+                Card::ALL
+                    .iter()
+                    .filter(natural)
+                    .max_by_key(|&&card| self.strength(card))
+            })
+            .copied();
+        return Hand(
+            hand.0
+                .iter()
+                .map(|&card| match target {
+                    Some(target) if self.wildcards[card as usize] => target,
+                    _ => card,
+                })
+                .collect(),
+        );
+    }
+
+    /// Card strengths in the order ties between equal hand types are broken.
+    fn tie_break_order(&self, hand: &Hand) -> Vec<u8> {
+        let strengths = hand
+            .0
+            .iter()
+            .map(|card| self.strength(*card))
+            .collect::<Vec<_>>();
+        if self.tie_break == TieBreak::Positional {
+            return strengths;
+        }
+        let count = |strength: u8| strengths.iter().filter(|&&s| s == strength).count();
+        let mut grouped = strengths.clone();
+        grouped.sort_by(|&a, &b| count(b).cmp(&count(a)).then(b.cmp(&a)));
+        return grouped;
     }
+
+    /// Packs the hand type and the tie-break strengths into one integer that
+    /// orders hands of the same size the same way as [`Rules::compare`]:
+    /// four bits per signature entry, zero padded to the hand size, then
+    /// four bits per card. Only defined up to [`MAX_PACKED_HAND_SIZE`] cards.
+    fn sort_key(&self, hand: &Hand) -> u128 {
+        assert!(
+            hand.0.len() <= MAX_PACKED_HAND_SIZE,
+            "Hand too large to pack"
+        );
+        let HandType(signature) = self.hand_type(hand);
+        let padding = std::iter::repeat_n(0, hand.0.len() - signature.len());
+        return signature
+            .into_iter()
+            .chain(padding)
+            .chain(self.tie_break_order(hand))
+            .fold(0, |key, nibble| key << 4 | nibble as u128);
+    }
+
+    fn compare(&self, a: &Hand, b: &Hand) -> Ordering {
+        return self
+            .hand_type(a)
+            .cmp(&self.hand_type(b))
+            .then_with(|| self.tie_break_order(a).cmp(&self.tie_break_order(b)));
+    }
+}
+
+/// A hand and its bid, along with the key it sorts by under some rules when
+/// it is small enough to pack.
+struct Entry {
+    key: Option<u128>,
+    hand: Hand,
+    bid: u64,
+}
+
+/// A hand whose size differs from the first hand's; types of hands with
+/// different sizes do not compare meaningfully.
+#[derive(PartialEq, Eq, Debug)]
+struct MixedHandSizes {
+    /// One-based.
+    line: usize,
+    expected: usize,
+    found: usize,
 }
 
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+impl Display for MixedHandSizes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {} holds {} cards, expected {} like the first hand",
+            self.line, self.found, self.expected
+        )
     }
 }
 
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> Ordering {
-        let self_type: HandType = self.clone().into();
-        let other_type: HandType = other.clone().into();
-        self_type
-            .cmp(&other_type)
-            .then_with(|| self.0.cmp(&other.0))
-            .then_with(|| self.1.cmp(&other.1))
-            .then_with(|| self.2.cmp(&other.2))
-            .then_with(|| self.3.cmp(&other.3))
-            .then_with(|| self.4.cmp(&other.4))
+impl std::error::Error for MixedHandSizes {}
+
+/// Parses every hand and sorts them from weakest to strongest.
+fn rank_hands(input: &str, rules: &Rules) -> Result<Vec<Entry>, MixedHandSizes> {
+    let mut set_of_hands = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let (hand_str, bid) = line.split_once(' ').unwrap();
+        let hand = parse_hand(hand_str);
+        let expected = set_of_hands
+            .first()
+            .map_or(hand.0.len(), |entry: &Entry| entry.hand.0.len());
+        if hand.0.len() != expected {
+            return Err(MixedHandSizes {
+                line: index + 1,
+                expected,
+                found: hand.0.len(),
+            });
+        }
+        set_of_hands.push(Entry {
+            key: (hand.0.len() <= MAX_PACKED_HAND_SIZE).then(|| rules.sort_key(&hand)),
+            hand,
+            bid: bid.trim().parse::<u64>().unwrap(),
+        });
     }
+    if set_of_hands.iter().all(|entry| entry.key.is_some()) {
+        set_of_hands.sort_unstable_by_key(|entry| entry.key);
+    } else {
+        set_of_hands.sort_by(|a, b| rules.compare(&a.hand, &b.hand));
+    }
+    return Ok(set_of_hands);
 }
 
-fn part1(input: &str) -> u64 {
-    let mut set_of_hands = input
-        .lines()
-        .map(|line| line.split_once(" ").unwrap())
-        .map(|(hand_str, bid)| {
-            (
-                parse_day1_hand(hand_str),
-                bid.trim().parse::<u64>().unwrap(),
-            )
-        })
-        .collect::<Vec<_>>();
-    set_of_hands.sort_by_key(|(hand, _)| *hand);
-    return set_of_hands
+fn total_winnings(input: &str, rules: &Rules) -> Result<u64, MixedHandSizes> {
+    return Ok(rank_hands(input, rules)?
         .into_iter()
         .enumerate()
-        .map(|(index, (_, bid))| {
+        .map(|(index, Entry { bid, .. })| {
             let index = index + 1;
             bid * index as u64
         })
-        .sum();
-}
-
-fn part2(input: &str) -> u64 {
-    let mut set_of_hands = input
-        .lines()
-        .map(|line| line.split_once(" ").unwrap())
-        .map(|(hand_str, bid)| {
-            (
-                parse_day2_hand(hand_str),
-                bid.trim().parse::<u64>().unwrap(),
-            )
-        })
+        .sum());
+}
+
+/// Every hand from weakest to strongest with its type, the wildcard
+/// substitution behind that type, and what it adds to the total.
+fn explain(input: &str, rules: &Rules) -> Result<String, MixedHandSizes> {
+    let entries = rank_hands(input, rules)?;
+    let width = entries
+        .iter()
+        .map(|entry| entry.hand.0.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let mut table = format!(
+        "{:>5}  {:<width$}  {:<15}  {:<width$}  {:>5}  {:>9}\n",
+        "Rank", "Hand", "Type", "Best", "Bid", "Winnings"
+    );
+    let mut total = 0;
+    for (index, Entry { hand, bid, .. }) in entries.into_iter().enumerate() {
+        let rank = index as u64 + 1;
+        total += bid * rank;
+        table.push_str(&format!(
+            "{:>5}  {:<width$}  {:<15}  {:<width$}  {:>5}  {:>9}\n",
+            rank,
+            hand.to_string(),
+            rules.hand_type(&hand).to_string(),
+            rules.substitution(&hand).to_string(),
+            bid,
+            bid * rank
+        ));
+    }
+    table.push_str(&format!("Total winnings: {}\n", total));
+    return Ok(table);
+}
+
+fn part1(input: &str) -> Result<u64, MixedHandSizes> {
+    return total_winnings(input, &Rules::standard());
+}
+
+fn part2(input: &str) -> Result<u64, MixedHandSizes> {
+    return total_winnings(input, &Rules::jokers());
+}
+
+/// Sorts a million pseudo-random hands by comparing them directly and by
+/// their precomputed keys.
+fn bench() {
+    let mut random = XorShift::default();
+    let mut random_card = || Card::ALL[random.below(13) as usize];
+    let hands = (0..1_000_000)
+        .map(|_| Hand((0..5).map(|_| random_card()).collect()))
         .collect::<Vec<_>>();
-    set_of_hands.sort_by_key(|(hand, _)| *hand);
-    return set_of_hands
-        .into_iter()
-        .enumerate()
-        .map(|(index, (_, bid))| {
-            let index = index + 1;
-            bid * index as u64
-        })
-        .sum();
+    let rules = Rules::jokers();
+
+    let mut compared = hands.clone();
+    let start = Instant::now();
+    compared.sort_by(|a, b| rules.compare(a, b));
+    let compare_time = start.elapsed();
+    black_box(&compared);
+
+    let start = Instant::now();
+    let mut keyed = hands
+        .iter()
+        .map(|hand| (rules.sort_key(hand), hand))
+        .collect::<Vec<_>>();
+    keyed.sort_unstable_by_key(|(key, _)| *key);
+    let key_time = start.elapsed();
+    black_box(&keyed);
+
+    println!("Rules::compare: {:?}", compare_time);
+    println!(
+        "Precomputed keys: {:?} ({:.1}x faster)",
+        key_time,
+        compare_time.as_secs_f64() / key_time.as_secs_f64()
+    );
 }
 
 #[cfg(test)]
 mod tests {
+    use std::cmp::Ordering;
+
+    use super::{parse_hand, Card, Hand, HandType, Rules, TieBreak};
+
     const INPUT: &str = "32T3K 765\n\
                         T55J5 684\n\
                         KK677 28\n\
@@ -238,11 +479,194 @@ mod tests {
 
     #[test]
     fn part1() {
-        assert_eq!(super::part1(INPUT), 6440);
+        assert_eq!(super::part1(INPUT), Ok(6440));
     }
 
     #[test]
     fn part2() {
-        assert_eq!(super::part2(INPUT), 5905);
+        assert_eq!(super::part2(INPUT), Ok(5905));
+    }
+
+    #[test]
+    fn custom_rules() {
+        let rules = Rules::new(&[Card::Jack, Card::Two], TieBreak::Positional);
+        assert_eq!(
+            rules.hand_type(&parse_hand("2J345")),
+            HandType(vec![3, 1, 1])
+        );
+        assert_eq!(rules.hand_type(&parse_hand("22JJ3")), HandType(vec![5]));
+        assert_eq!(
+            rules.compare(&parse_hand("2KKKK"), &parse_hand("JKKKK")),
+            Ordering::Greater
+        );
+
+        let (a, b) = (parse_hand("23332"), parse_hand("32223"));
+        assert_eq!(Rules::standard().compare(&a, &b), Ordering::Less);
+        let poker = Rules::new(&[], TieBreak::Grouped);
+        assert_eq!(poker.compare(&a, &b), Ordering::Greater);
+        assert_eq!(
+            poker.compare(&parse_hand("2KK3A"), &parse_hand("QQA32")),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn sort_keys() {
+        let hands = INPUT
+            .lines()
+            .map(|line| parse_hand(line.split_once(' ').unwrap().0))
+            .collect::<Vec<_>>();
+        for rules in [
+            Rules::standard(),
+            Rules::jokers(),
+            Rules::new(&[Card::Two], TieBreak::Grouped),
+        ] {
+            for a in &hands {
+                for b in &hands {
+                    assert_eq!(
+                        rules.sort_key(a).cmp(&rules.sort_key(b)),
+                        rules.compare(a, b)
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            Rules::standard().sort_key(&parse_hand("32T3K")),
+            0x211101081b
+        );
+    }
+
+    #[test]
+    fn other_hand_sizes() {
+        let rules = Rules::standard();
+        assert_eq!(
+            super::total_winnings("KK2 10\nAAA 5\n23J 7", &rules),
+            Ok(42)
+        );
+        assert_eq!(
+            super::total_winnings("KK2 10\nAAAAA 5", &rules),
+            Err(super::MixedHandSizes {
+                line: 2,
+                expected: 3,
+                found: 5,
+            })
+        );
+        assert_eq!(
+            super::part1("KK2 10\nAAAAA 5").unwrap_err().to_string(),
+            "line 2 holds 5 cards, expected 3 like the first hand"
+        );
+        // Too large to pack, so these are compared directly.
+        let large = "22222222223333333 1\nAAAAAAAAAAAAAAAAK 2\nKKKKKKKKKKKKKKKKK 3";
+        assert_eq!(super::total_winnings(large, &rules), Ok(1 + 2 * 2 + 3 * 3));
+        assert_eq!(
+            super::total_winnings(large, &Rules::new(&[Card::King], TieBreak::Positional)),
+            Ok(1 + 2 * 3 + 3 * 2)
+        );
+        assert_eq!(
+            rules.hand_type(&parse_hand("AAAKKK2")),
+            HandType(vec![3, 3, 1])
+        );
+        assert_eq!(rules.hand_type(&parse_hand("AAAKKK2")).to_string(), "3+3+1");
+        let mut hands = ["2222333", "AAAAKK2", "AAAKKQQ", "AAAKKK2"].map(parse_hand);
+        hands.sort_by_key(|hand| rules.sort_key(hand));
+        assert_eq!(
+            hands,
+            ["AAAKKQQ", "AAAKKK2", "AAAAKK2", "2222333"].map(parse_hand)
+        );
+        let jokers = Rules::jokers();
+        assert_eq!(
+            jokers.hand_type(&parse_hand("AJKKQQ2")),
+            HandType(vec![3, 2, 1, 1])
+        );
+    }
+
+    #[test]
+    fn explain() {
+        let table = super::explain(INPUT, &Rules::jokers()).unwrap();
+        let lines = table.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            " Rank  Hand   Type             Best     Bid   Winnings"
+        );
+        assert_eq!(
+            lines[1],
+            "    1  32T3K  One pair         32T3K    765        765"
+        );
+        assert_eq!(
+            lines[5],
+            "    5  KTJJT  Four of a kind   KTTTT    220       1100"
+        );
+        assert_eq!(lines[6], "Total winnings: 5905");
+    }
+
+    #[test]
+    fn substitution() {
+        let rules = Rules::new(&[Card::Jack, Card::Two], TieBreak::Positional);
+        assert_eq!(
+            rules.substitution(&parse_hand("2J3KK")),
+            parse_hand("KK3KK")
+        );
+        assert_eq!(
+            rules.substitution(&parse_hand("J2J2J")),
+            parse_hand("AAAAA")
+        );
+        assert_eq!(
+            Rules::standard().substitution(&parse_hand("JJ234")),
+            parse_hand("JJ234")
+        );
+    }
+
+    /// Classifies a hand without wildcards by counting pairs and triples.
+    fn naive_hand_type(cards: [Card; 5]) -> HandType {
+        let counts = cards.map(|card| cards.iter().filter(|&&c| c == card).count());
+        let pairs = counts.iter().filter(|&&count| count == 2).count() / 2;
+        let triples = counts.contains(&3);
+        let signature: &[u8] = match (counts.iter().max(), triples, pairs) {
+            (Some(5), _, _) => &[5],
+            (Some(4), _, _) => &[4, 1],
+            (_, true, 1) => &[3, 2],
+            (_, true, _) => &[3, 1, 1],
+            (_, _, 2) => &[2, 2, 1],
+            (_, _, 1) => &[2, 1, 1, 1],
+            _ => &[1, 1, 1, 1, 1],
+        };
+        return HandType(signature.to_vec());
+    }
+
+    /// Best hand type over every way of replacing each joker by another card.
+    fn best_substitution(cards: [Card; 5]) -> HandType {
+        match cards.iter().position(|&card| card == Card::Jack) {
+            None => naive_hand_type(cards),
+            Some(joker) => Card::ALL
+                .iter()
+                .filter(|&&card| card != Card::Jack)
+                .map(|&card| {
+                    let mut cards = cards;
+                    cards[joker] = card;
+                    best_substitution(cards)
+                })
+                .max()
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn classify_every_hand() {
+        // The oracle only depends on which cards are held, so share it
+        // between orderings of the same cards.
+        let rules = Rules::jokers();
+        let mut oracle = std::collections::HashMap::new();
+        for index in 0..13usize.pow(5) {
+            let cards: [Card; 5] = std::array::from_fn(|position| {
+                Card::ALL[index / 13usize.pow(position as u32) % 13]
+            });
+            let hand = Hand(cards.to_vec());
+            let mut sorted = cards;
+            sorted.sort();
+            let expected = oracle
+                .entry(sorted)
+                .or_insert_with(|| best_substitution(sorted));
+            assert_eq!(&rules.hand_type(&hand), expected, "{:?}", cards);
+        }
     }
 }
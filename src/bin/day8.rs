@@ -1,4 +1,9 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    hint::black_box,
+    time::Instant,
+};
 
 use nom::{
     branch::alt,
@@ -9,13 +14,46 @@ use nom::{
     sequence::tuple,
     IResult,
 };
-use num::integer;
+use num::Integer;
+use petgraph::{
+    dot::{Config, Dot},
+    graph::{DiGraph, NodeIndex},
+    visit::{Dfs, EdgeRef},
+};
 
 fn main() {
     let input = include_str!("../../input/day8.txt");
 
-    println!("Part 1: {}", part1(&input));
-    println!("Part 2: {}", part2(&input));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let pattern = |name: &str, default: &str| {
+        let value = args
+            .iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+            .map_or(default, String::as_str);
+        NodePattern::parse(value)
+    };
+    match args.first().map(String::as_str) {
+        Some("bench") => bench(input),
+        Some("walk") => explore(input, &pattern("--from", "AAA"), &pattern("--to", "ZZZ")),
+        Some("ghosts") => explore_ghosts(input, &pattern("--from", "*A"), &pattern("--to", "*Z")),
+        Some("dot") => export_dot(input, &pattern("--from", "*A")),
+        Some("scc") => report_components(input),
+        _ => solve(input),
+    }
+}
+
+fn solve(input: &str) {
+    match part1(input) {
+        Ok(Walk::Arrived(steps)) => println!("Part 1: {}", steps),
+        Ok(Walk::Unreachable) => println!("Part 1: ZZZ is unreachable from AAA"),
+        Err(error) => println!("Part 1: {}", error),
+    }
+    match part2(input) {
+        Ok(Some(steps)) => println!("Part 2: {}", steps),
+        Ok(None) => println!("Part 2: the ghosts never meet on ..Z nodes"),
+        Err(error) => println!("Part 2: {}", error),
+    }
 }
 
 #[derive(Debug, Eq, Ord, PartialEq, PartialOrd, Clone, Copy)]
@@ -24,6 +62,15 @@ enum Instruction {
     Right,
 }
 
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Left => write!(f, "L"),
+            Instruction::Right => write!(f, "R"),
+        }
+    }
+}
+
 fn parse_instructions(input: &str) -> IResult<&str, Vec<Instruction>> {
     return many1(map_res(alt((tag("L"), tag("R"))), |s: &str| match s {
         "L" => Ok(Instruction::Left),
@@ -57,37 +104,492 @@ fn parse_input(input: &str) -> IResult<&str, (Vec<Instruction>, Map)> {
     return Ok((input, (instructions, map)));
 }
 
-fn part1(input: &str) -> u64 {
-    let (instructions, map) = parse_input(input).unwrap().1;
-    let mut steps = 0;
-    let mut current = "AAA";
-    for instruction in instructions.iter().cycle() {
-        if current == "ZZZ" {
-            return steps;
+#[derive(Debug, PartialEq, Eq)]
+enum NetworkError<'a> {
+    Parse(String),
+    UndefinedNode { from: &'a str, to: &'a str },
+    MissingNode(&'a str),
+}
+
+impl Display for NetworkError<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkError::Parse(error) => write!(f, "invalid network: {}", error),
+            NetworkError::UndefinedNode { from, to } => {
+                write!(f, "{} points to undefined node {}", from, to)
+            }
+            NetworkError::MissingNode(node) => write!(f, "node {} is not defined", node),
         }
-        let next = map.get(&(current, *instruction)).unwrap();
-        current = next;
-        steps += 1;
     }
-    return steps;
 }
-fn part2(input: &str) -> u64 {
-    let (instructions, map) = parse_input(input).unwrap().1;
-    let solution = map
-        .keys()
-        .filter_map(|(a, _)| a.ends_with('A').then_some(a))
-        .map(|point| {
-            let mut start = point;
-            for (index, instruction) in instructions.iter().cycle().enumerate() {
-                if start.ends_with('Z') {
-                    return index as u64;
-                }
-                start = map.get(&(start, *instruction)).unwrap();
+
+impl std::error::Error for NetworkError<'_> {}
+
+/// The network with every node replaced by its index in `names`, so a step
+/// is a plain array lookup.
+struct Network<'a> {
+    names: Vec<&'a str>,
+    ids: HashMap<&'a str, u32>,
+    next: Vec<[u32; 2]>,
+}
+
+impl<'a> Network<'a> {
+    /// Every node `map` points to must be defined.
+    fn compile(map: &Map<'a>) -> Self {
+        let names = map
+            .keys()
+            .filter_map(|&(name, instruction)| (instruction == Instruction::Left).then_some(name))
+            .collect::<Vec<_>>();
+        let ids = names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (*name, id as u32))
+            .collect::<HashMap<_, _>>();
+        let next = names
+            .iter()
+            .map(|name| {
+                [Instruction::Left, Instruction::Right]
+                    .map(|instruction| ids[map[&(*name, instruction)]])
+            })
+            .collect();
+        return Network { names, ids, next };
+    }
+
+    fn len(&self) -> usize {
+        return self.names.len();
+    }
+
+    fn id(&self, name: &str) -> Option<u32> {
+        return self.ids.get(name).copied();
+    }
+
+    fn name(&self, id: u32) -> &'a str {
+        return self.names[id as usize];
+    }
+
+    fn step(&self, id: u32, instruction: Instruction) -> u32 {
+        return self.next[id as usize][instruction as usize];
+    }
+
+    fn ids_matching(&self, pattern: &NodePattern) -> Vec<u32> {
+        return (0..self.len() as u32)
+            .filter(|&id| pattern.matches(self.name(id)))
+            .collect();
+    }
+
+    /// Whether each node, by id, matches `pattern`.
+    fn mask(&self, pattern: &NodePattern) -> Vec<bool> {
+        return self
+            .names
+            .iter()
+            .map(|name| pattern.matches(name))
+            .collect();
+    }
+
+    /// Node indices match the ids, and every node has one `L` and one `R`
+    /// edge.
+    fn to_graph(&self) -> DiGraph<&'a str, Instruction> {
+        let mut graph = DiGraph::with_capacity(self.len(), self.len() * 2);
+        for name in &self.names {
+            graph.add_node(*name);
+        }
+        for (id, next) in self.next.iter().enumerate() {
+            for (instruction, to) in [Instruction::Left, Instruction::Right]
+                .into_iter()
+                .zip(next)
+            {
+                graph.add_edge(
+                    NodeIndex::new(id),
+                    NodeIndex::new(*to as usize),
+                    instruction,
+                );
+            }
+        }
+        return graph;
+    }
+}
+
+/// Selects nodes by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum NodePattern {
+    Exact(String),
+    Suffix(String),
+    /// `*` matches any run of characters and `?` any single one.
+    Glob(String),
+}
+
+impl NodePattern {
+    /// `exact:`, `suffix:` and `glob:` prefixes pick the kind explicitly,
+    /// otherwise anything with a wildcard is a glob.
+    fn parse(pattern: &str) -> Self {
+        if let Some(name) = pattern.strip_prefix("exact:") {
+            return NodePattern::Exact(name.to_string());
+        }
+        if let Some(suffix) = pattern.strip_prefix("suffix:") {
+            return NodePattern::Suffix(suffix.to_string());
+        }
+        if let Some(glob) = pattern.strip_prefix("glob:") {
+            return NodePattern::Glob(glob.to_string());
+        }
+        if pattern.contains(['*', '?']) {
+            return NodePattern::Glob(pattern.to_string());
+        }
+        return NodePattern::Exact(pattern.to_string());
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        return match self {
+            NodePattern::Exact(exact) => name == exact,
+            NodePattern::Suffix(suffix) => name.ends_with(suffix.as_str()),
+            NodePattern::Glob(glob) => glob_matches(
+                &glob.chars().collect::<Vec<_>>(),
+                &name.chars().collect::<Vec<_>>(),
+            ),
+        };
+    }
+}
+
+impl Display for NodePattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodePattern::Exact(exact) => write!(f, "{}", exact),
+            NodePattern::Suffix(suffix) => write!(f, "*{}", suffix),
+            NodePattern::Glob(glob) => write!(f, "{}", glob),
+        }
+    }
+}
+
+fn glob_matches(glob: &[char], name: &[char]) -> bool {
+    return match (glob.split_first(), name.split_first()) {
+        (None, _) => name.is_empty(),
+        (Some(('*', rest)), _) => {
+            glob_matches(rest, name) || (!name.is_empty() && glob_matches(glob, &name[1..]))
+        }
+        (Some(_), None) => false,
+        (Some(('?', rest)), Some((_, name))) => glob_matches(rest, name),
+        (Some((expected, rest)), Some((c, name))) => expected == c && glob_matches(rest, name),
+    };
+}
+
+/// Parses the network and checks that every node it points to is defined.
+fn parse_network(input: &str) -> Result<(Vec<Instruction>, Network<'_>), NetworkError<'_>> {
+    let (rest, (instructions, map)) =
+        parse_input(input).map_err(|error| NetworkError::Parse(error.to_string()))?;
+    if let Some(line) = rest.lines().find(|line| !line.trim().is_empty()) {
+        return Err(NetworkError::Parse(format!("unexpected line {:?}", line)));
+    }
+    for (&(from, _), &to) in &map {
+        if !map.contains_key(&(to, Instruction::Left)) {
+            return Err(NetworkError::UndefinedNode { from, to });
+        }
+    }
+    return Ok((instructions, Network::compile(&map)));
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Walk {
+    Arrived(u64),
+    /// The walk came back to a `(node, instruction index)` state it had
+    /// already been in without passing through the target.
+    Unreachable,
+}
+
+fn part1(input: &str) -> Result<Walk, NetworkError<'_>> {
+    let (instructions, network) = parse_network(input)?;
+    let start = network.id("AAA").ok_or(NetworkError::MissingNode("AAA"))?;
+    let end = network.id("ZZZ").ok_or(NetworkError::MissingNode("ZZZ"))?;
+    let mut is_end = vec![false; network.len()];
+    is_end[end as usize] = true;
+    return Ok(walk(&instructions, &network, start, &is_end));
+}
+
+/// Follows the instructions from `start` until it reaches a node marked in
+/// `is_end`.
+fn walk(instructions: &[Instruction], network: &Network, start: u32, is_end: &[bool]) -> Walk {
+    let mut visited = vec![false; network.len() * instructions.len()];
+    let mut current = start;
+    for (steps, (index, instruction)) in instructions.iter().enumerate().cycle().enumerate() {
+        if is_end[current as usize] {
+            return Walk::Arrived(steps as u64);
+        }
+        let state = current as usize * instructions.len() + index;
+        if visited[state] {
+            return Walk::Unreachable;
+        }
+        visited[state] = true;
+        current = network.step(current, *instruction);
+    }
+    unreachable!("the instructions cycle forever")
+}
+
+/// A ghost's walk over `(node, instruction index)` states: after `tail`
+/// steps it repeats every `length` steps. `end_offsets` are the steps at
+/// which it stands on an end node; the ones `>= tail` recur every `length`
+/// steps.
+#[derive(Debug, PartialEq, Eq)]
+struct Cycle {
+    tail: u64,
+    length: u64,
+    end_offsets: Vec<u64>,
+}
+
+impl Cycle {
+    fn is_end_at(&self, step: u64) -> bool {
+        if step < self.tail {
+            return self.end_offsets.contains(&step);
+        }
+        return self.end_offsets.iter().any(|&offset| {
+            offset >= self.tail && step >= offset && (step - offset).is_multiple_of(self.length)
+        });
+    }
+}
+
+fn find_cycle(
+    instructions: &[Instruction],
+    network: &Network,
+    start: u32,
+    is_end: &[bool],
+) -> Cycle {
+    let mut seen = vec![None; network.len() * instructions.len()];
+    let mut end_offsets = Vec::new();
+    let mut current = start;
+    for step in 0.. {
+        let index = step as usize % instructions.len();
+        let state = current as usize * instructions.len() + index;
+        if let Some(first) = seen[state] {
+            return Cycle {
+                tail: first,
+                length: step - first,
+                end_offsets,
+            };
+        }
+        seen[state] = Some(step);
+        if is_end[current as usize] {
+            end_offsets.push(step);
+        }
+        current = network.step(current, instructions[index]);
+    }
+    unreachable!("the state space is finite")
+}
+
+/// Solves `x = a (mod m)` for both congruences at once, if possible.
+fn crt((a1, m1): (i128, i128), (a2, m2): (i128, i128)) -> Option<(i128, i128)> {
+    let gcd = m1.extended_gcd(&m2);
+    if (a2 - a1) % gcd.gcd != 0 {
+        return None;
+    }
+    let lcm = m1 / gcd.gcd * m2;
+    let x = a1 + m1 * ((a2 - a1) / gcd.gcd * gcd.x % (m2 / gcd.gcd));
+    return Some((x.rem_euclid(lcm), lcm));
+}
+
+/// Earliest step at which every ghost stands on an end node at once.
+fn earliest_meeting(cycles: &[Cycle]) -> Option<u64> {
+    let settled = cycles.iter().map(|cycle| cycle.tail).max()?;
+    // Before every ghost settles into its loop, just check each step.
+    if let Some(step) = (0..settled).find(|&step| cycles.iter().all(|c| c.is_end_at(step))) {
+        return Some(step);
+    }
+    // Afterwards, pick one looping end offset per ghost and combine them.
+    let mut candidates = vec![(0i128, 1i128)];
+    for cycle in cycles {
+        let residues = cycle
+            .end_offsets
+            .iter()
+            .filter(|&&offset| offset >= cycle.tail)
+            .map(|&offset| ((offset % cycle.length) as i128, cycle.length as i128))
+            .collect::<Vec<_>>();
+        candidates = candidates
+            .into_iter()
+            .flat_map(|candidate| residues.iter().filter_map(move |&r| crt(candidate, r)))
+            .collect();
+    }
+    return candidates
+        .into_iter()
+        .map(|(x, modulus)| {
+            let settled = settled as i128;
+            let periods = ((settled - x).max(0) + modulus - 1) / modulus;
+            (x + periods * modulus) as u64
+        })
+        .min();
+}
+
+fn part2(input: &str) -> Result<Option<u64>, NetworkError<'_>> {
+    let (instructions, network) = parse_network(input)?;
+    return Ok(simultaneous_arrival(
+        &instructions,
+        &network,
+        &NodePattern::Suffix("A".to_string()),
+        &NodePattern::Suffix("Z".to_string()),
+    ));
+}
+
+/// Earliest step at which ghosts starting on every node matching `starts`
+/// all stand on nodes matching `ends`.
+fn simultaneous_arrival(
+    instructions: &[Instruction],
+    network: &Network,
+    starts: &NodePattern,
+    ends: &NodePattern,
+) -> Option<u64> {
+    let is_end = network.mask(ends);
+    let cycles = network
+        .ids_matching(starts)
+        .into_iter()
+        .map(|id| find_cycle(instructions, network, id, &is_end))
+        .collect::<Vec<_>>();
+    return earliest_meeting(&cycles);
+}
+
+fn explore(input: &str, starts: &NodePattern, ends: &NodePattern) {
+    let (instructions, network) = match parse_network(input) {
+        Ok(parsed) => parsed,
+        Err(error) => return println!("{}", error),
+    };
+    let is_end = network.mask(ends);
+    for start in network.ids_matching(starts) {
+        match walk(&instructions, &network, start, &is_end) {
+            Walk::Arrived(steps) => {
+                println!("{} -> {}: {} steps", network.name(start), ends, steps)
             }
-            unreachable!("No path to Z found")
+            Walk::Unreachable => println!("{} -> {}: unreachable", network.name(start), ends),
+        }
+    }
+}
+
+fn explore_ghosts(input: &str, starts: &NodePattern, ends: &NodePattern) {
+    let (instructions, network) = match parse_network(input) {
+        Ok(parsed) => parsed,
+        Err(error) => return println!("{}", error),
+    };
+    match simultaneous_arrival(&instructions, &network, starts, ends) {
+        Some(steps) => println!("{} -> {}: {} steps", starts, ends, steps),
+        None => println!("{} -> {}: the ghosts never meet", starts, ends),
+    }
+}
+
+const GHOST_COLORS: &[&str] = &["red", "blue", "darkgreen", "orange", "purple", "brown"];
+
+/// Renders the network as DOT, colouring the subgraph reachable from each of
+/// `ghosts` (the first ghost wins where they overlap).
+fn to_dot(graph: &DiGraph<&str, Instruction>, ghosts: &[u32]) -> String {
+    let mut owner = vec![None; graph.node_count()];
+    for (ghost, start) in ghosts.iter().enumerate() {
+        let mut dfs = Dfs::new(graph, NodeIndex::new(*start as usize));
+        while let Some(node) = dfs.next(graph) {
+            owner[node.index()].get_or_insert(ghost);
+        }
+    }
+    let color =
+        |node: NodeIndex| owner[node.index()].map(|ghost| GHOST_COLORS[ghost % GHOST_COLORS.len()]);
+    let edge_attributes = |_, edge: petgraph::graph::EdgeReference<Instruction>| {
+        let color = color(edge.source())
+            .filter(|_| owner[edge.source().index()] == owner[edge.target().index()])
+            .map_or(String::new(), |color| format!(", color = {}", color));
+        format!("label = \"{}\"{}", edge.weight(), color)
+    };
+    let node_attributes = |_, (node, name): (NodeIndex, &&str)| {
+        let start = ghosts.contains(&(node.index() as u32));
+        let color = color(node).map_or(String::new(), |color| format!(", color = {}", color));
+        let shape = if start { ", shape = doublecircle" } else { "" };
+        format!("label = \"{}\"{}{}", name, color, shape)
+    };
+    let dot = Dot::with_attr_getters(
+        graph,
+        &[Config::EdgeNoLabel, Config::NodeNoLabel],
+        &edge_attributes,
+        &node_attributes,
+    );
+    return format!("{:?}", dot);
+}
+
+/// Strongly connected components by node name, largest first.
+fn strongly_connected_components<'a>(graph: &DiGraph<&'a str, Instruction>) -> Vec<Vec<&'a str>> {
+    let mut components = petgraph::algo::tarjan_scc(graph)
+        .into_iter()
+        .map(|component| {
+            let mut names = component
+                .into_iter()
+                .map(|node| graph[node])
+                .collect::<Vec<_>>();
+            names.sort();
+            names
         })
-        .fold(1, integer::lcm);
-    return solution;
+        .collect::<Vec<_>>();
+    components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    return components;
+}
+
+fn export_dot(input: &str, ghosts: &NodePattern) {
+    let (_, network) = match parse_network(input) {
+        Ok(parsed) => parsed,
+        Err(error) => return println!("{}", error),
+    };
+    println!(
+        "{}",
+        to_dot(&network.to_graph(), &network.ids_matching(ghosts))
+    );
+}
+
+fn report_components(input: &str) {
+    let (_, network) = match parse_network(input) {
+        Ok(parsed) => parsed,
+        Err(error) => return println!("{}", error),
+    };
+    let components = strongly_connected_components(&network.to_graph());
+    println!("{} strongly connected components", components.len());
+    for component in components.iter().filter(|component| component.len() > 1) {
+        println!("{} nodes: {}", component.len(), component.join(", "));
+    }
+}
+
+/// Times the `AAA` to `ZZZ` walk on the string-keyed map against the
+/// compiled network.
+fn bench(input: &str) {
+    const RUNS: u32 = 100;
+    let (instructions, map) = parse_input(input).unwrap().1;
+    let network = Network::compile(&map);
+
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        let mut steps = 0u64;
+        let mut current = "AAA";
+        for instruction in instructions.iter().cycle() {
+            if current == "ZZZ" {
+                break;
+            }
+            current = map[&(current, *instruction)];
+            steps += 1;
+        }
+        black_box(steps);
+    }
+    let map_time = start.elapsed() / RUNS;
+
+    let (from, to) = (network.id("AAA").unwrap(), network.id("ZZZ").unwrap());
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        let mut steps = 0u64;
+        let mut current = from;
+        for instruction in instructions.iter().cycle() {
+            if current == to {
+                break;
+            }
+            current = network.step(current, *instruction);
+            steps += 1;
+        }
+        black_box(steps);
+    }
+    let network_time = start.elapsed() / RUNS;
+
+    println!(
+        "BTreeMap<(&str, Instruction), &str>: {:?} per walk",
+        map_time
+    );
+    println!(
+        "Vec<[u32; 2]>: {:?} per walk ({:.1}x faster)",
+        network_time,
+        map_time.as_secs_f64() / network_time.as_secs_f64()
+    );
 }
 
 #[cfg(test)]
@@ -112,10 +614,192 @@ mod tests {
 
     #[test]
     fn part1() {
-        assert_eq!(super::part1(INPUT_PART_1), 6);
+        assert_eq!(super::part1(INPUT_PART_1), Ok(super::Walk::Arrived(6)));
     }
     #[test]
     fn part2() {
-        assert_eq!(super::part2(INPUT_PART_2), 6);
+        assert_eq!(super::part2(INPUT_PART_2), Ok(Some(6)));
+    }
+
+    #[test]
+    fn compile_network() {
+        let (_, network) = super::parse_network(INPUT_PART_1).unwrap();
+        assert_eq!(network.len(), 3);
+        let bbb = network.id("BBB").unwrap();
+        assert_eq!(network.name(bbb), "BBB");
+        assert_eq!(
+            network.name(network.step(bbb, super::Instruction::Left)),
+            "AAA"
+        );
+        assert_eq!(
+            network.name(network.step(bbb, super::Instruction::Right)),
+            "ZZZ"
+        );
+        assert_eq!(network.id("CCC"), None);
+    }
+
+    #[test]
+    fn node_patterns() {
+        use super::NodePattern::{self, *};
+        assert_eq!(NodePattern::parse("AAA"), Exact("AAA".to_string()));
+        assert_eq!(NodePattern::parse("suffix:Z"), Suffix("Z".to_string()));
+        assert_eq!(NodePattern::parse("1?A"), Glob("1?A".to_string()));
+        assert!(NodePattern::parse("*Z").matches("11Z"));
+        assert!(NodePattern::parse("2*").matches("22B"));
+        assert!(NodePattern::parse("?2?").matches("22B"));
+        assert!(!NodePattern::parse("?2?").matches("22"));
+        assert!(!NodePattern::parse("*Z").matches("ZZA"));
+        assert!(Suffix("Z".to_string()).matches("11Z"));
+        assert!(!Exact("11".to_string()).matches("11Z"));
+    }
+
+    #[test]
+    fn custom_predicates() {
+        use super::NodePattern;
+        let (instructions, network) = super::parse_network(INPUT_PART_2).unwrap();
+        let start = network.id("11A").unwrap();
+        let is_end = network.mask(&NodePattern::parse("22?"));
+        assert_eq!(
+            super::walk(&instructions, &network, start, &is_end),
+            super::Walk::Unreachable
+        );
+        let is_end = network.mask(&NodePattern::parse("exact:11Z"));
+        assert_eq!(
+            super::walk(&instructions, &network, start, &is_end),
+            super::Walk::Arrived(2)
+        );
+        assert_eq!(
+            super::simultaneous_arrival(
+                &instructions,
+                &network,
+                &NodePattern::parse("*A"),
+                &NodePattern::parse("*B")
+            ),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn graph_export() {
+        let (_, network) = super::parse_network(INPUT_PART_2).unwrap();
+        let graph = network.to_graph();
+        assert_eq!(graph.node_count(), 8);
+        assert_eq!(graph.edge_count(), 16);
+        let components = super::strongly_connected_components(&graph);
+        assert_eq!(
+            components,
+            vec![
+                vec!["22B", "22C", "22Z"],
+                vec!["11B", "11Z"],
+                vec!["11A"],
+                vec!["22A"],
+                vec!["XXX"]
+            ]
+        );
+        let ghosts = network.ids_matching(&super::NodePattern::parse("*A"));
+        let dot = super::to_dot(&graph, &ghosts);
+        assert!(dot.contains("label = \"11A\", color = red, shape = doublecircle"));
+        assert!(dot.contains("label = \"22C\", color = blue"));
+        assert!(dot.contains("label = \"XXX\", color = red"));
+        assert!(dot.contains("label = \"R\", color = blue"));
+    }
+
+    #[test]
+    fn unreachable() {
+        const INPUT: &str = "\
+        LR\n\
+        \n\
+        AAA = (BBB, AAA)\n\
+        BBB = (AAA, BBB)\n\
+        ZZZ = (ZZZ, ZZZ)";
+        assert_eq!(super::part1(INPUT), Ok(super::Walk::Unreachable));
+    }
+
+    #[test]
+    fn invalid_network() {
+        use super::NetworkError::*;
+        assert_eq!(
+            super::part1("L\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)"),
+            Err(UndefinedNode {
+                from: "AAA",
+                to: "BBB"
+            })
+        );
+        assert_eq!(
+            super::part1("L\n\nAAA = (AAA, AAA)"),
+            Err(MissingNode("ZZZ"))
+        );
+        assert!(matches!(
+            super::part2("L\n\nAAA = (AAA, AAA)\nBBB = ZZZ"),
+            Err(Parse(_))
+        ));
+    }
+
+    #[test]
+    fn cycles() {
+        let (instructions, network) = super::parse_network(INPUT_PART_2).unwrap();
+        assert_eq!(
+            super::find_cycle(
+                &instructions,
+                &network,
+                network.id("22A").unwrap(),
+                &network.mask(&super::NodePattern::Suffix("Z".to_string()))
+            ),
+            super::Cycle {
+                tail: 1,
+                length: 6,
+                end_offsets: vec![3, 6],
+            }
+        );
+    }
+
+    #[test]
+    fn misaligned_cycles() {
+        // Plain LCM of the first ..Z steps (2 and 1) would answer 2.
+        const INPUT: &str = "\
+        L\n\
+        \n\
+        11A = (11B, 11B)\n\
+        11B = (11Z, 11Z)\n\
+        11Z = (11C, 11C)\n\
+        11C = (11D, 11D)\n\
+        11D = (11Z, 11Z)\n\
+        22A = (22Z, 22Z)\n\
+        22Z = (22B, 22B)\n\
+        22B = (22Z, 22Z)";
+        assert_eq!(super::part2(INPUT), Ok(Some(5)));
+    }
+
+    #[test]
+    fn different_tails() {
+        // The first ghost settles after one step, the second after three, so
+        // early steps sit before the first ghost's end offset.
+        const INPUT: &str = "\
+        L\n\
+        \n\
+        11A = (11B, 11B)\n\
+        11B = (11C, 11C)\n\
+        11C = (11Z, 11Z)\n\
+        11Z = (11B, 11B)\n\
+        22A = (22B, 22B)\n\
+        22B = (22C, 22C)\n\
+        22C = (22D, 22D)\n\
+        22D = (22Z, 22Z)\n\
+        22Z = (22D, 22D)";
+        assert_eq!(super::part2(INPUT), Ok(Some(6)));
+    }
+
+    #[test]
+    fn never_meeting() {
+        const INPUT: &str = "\
+        L\n\
+        \n\
+        11A = (11B, 11B)\n\
+        11B = (11Z, 11Z)\n\
+        11Z = (11B, 11B)\n\
+        22A = (22Z, 22Z)\n\
+        22Z = (22B, 22B)\n\
+        22B = (22Z, 22Z)";
+        assert_eq!(super::part2(INPUT), Ok(None));
     }
 }
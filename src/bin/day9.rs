@@ -1,64 +1,360 @@
+use std::fmt::Display;
+
+use num::{BigInt, BigRational, One, Signed, Zero};
+
 fn main() {
     let input = include_str!("../../input/day9.txt");
-    println!("Part 1: {}", part1(input));
-    println!("Part 2: {}", part2(input));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let argument = |usage: &str| -> i64 {
+        args.get(1)
+            .and_then(|argument| argument.parse().ok())
+            .expect(usage)
+    };
+    let each_history = |f: &dyn Fn(&[i64])| {
+        for (index, line) in input.lines().enumerate() {
+            match parse_history(line).and_then(|history| exact_degree(&history).map(|_| history)) {
+                Ok(history) => f(&history),
+                Err(error) => println!("line {}: {}", index + 1, error),
+            }
+        }
+    };
+    match args.first().map(String::as_str) {
+        Some("next") => {
+            let count = argument("usage: day9 next <count>") as usize;
+            each_history(&|history| println!("{}", join(&next_values(history, count))));
+        }
+        Some("previous") => {
+            let count = argument("usage: day9 previous <count>") as usize;
+            each_history(&|history| println!("{}", join(&previous_values(history, count))));
+        }
+        Some("at") => {
+            let index = argument("usage: day9 at <index>");
+            each_history(&|history| println!("{}", value_at(history, index)));
+        }
+        Some("polynomial") => each_history(&|history| match fit_polynomial(history) {
+            Ok(polynomial) => println!("degree {}: {}", polynomial.degree(), polynomial),
+            Err(error) => println!("error: {}", error),
+        }),
+        Some("check") => {
+            for (line, classification) in diagnose(input) {
+                match classification {
+                    Ok(degree) => println!("line {}: polynomial of degree {}", line, degree),
+                    Err(error) => println!("line {}: {}", line, error),
+                }
+            }
+        }
+        _ => {
+            report(1, part1(input));
+            report(2, part2(input));
+        }
+    }
 }
 
-fn part1(input: &str) -> i64 {
-    input
+fn report(part: u8, result: Result<i64, LineError>) {
+    match result {
+        Ok(total) => println!("Part {}: {}", part, total),
+        Err((line, error)) => println!("Part {}: line {}: {}", part, line, error),
+    }
+}
+
+fn parse_history(line: &str) -> Result<Vec<i64>, HistoryError> {
+    return line
+        .split_whitespace()
+        .map(|n| {
+            n.parse::<i64>()
+                .map_err(|_| HistoryError::InvalidNumber(n.to_string()))
+        })
+        .collect();
+}
+
+fn join(values: &[Value]) -> String {
+    return values
+        .iter()
+        .map(Value::to_string)
+        .collect::<Vec<_>>()
+        .join(" ");
+}
+
+/// An extrapolated value. Stays an `i64` unless the computation overflowed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Small(i64),
+    Big(BigInt),
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Small(value) => write!(f, "{}", value),
+            Value::Big(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// First element of every row of the difference pyramid, stopping at the
+/// first all-zero row. `None` if a difference overflows.
+fn forward_differences(history: &[i64]) -> Option<Vec<i64>> {
+    let mut differences = Vec::new();
+    let mut current = history.to_vec();
+    while !current.iter().all(|n| *n == 0) {
+        differences.push(current[0]);
+        current = current
+            .windows(2)
+            .map(|pair| pair[1].checked_sub(pair[0]))
+            .collect::<Option<Vec<_>>>()?;
+    }
+    return Some(differences);
+}
+
+/// Newton's forward difference formula:
+/// `f(index) = sum(binomial(index, k) * differences[k])`, where `history[0]`
+/// sits at index 0. Negative indices extrapolate backwards.
+fn checked_value_at(history: &[i64], index: i64) -> Option<i64> {
+    let mut binomial = 1i64;
+    let mut value = 0i64;
+    for (k, difference) in forward_differences(history)?.into_iter().enumerate() {
+        if k > 0 {
+            let k = k as i64;
+            binomial = binomial.checked_mul(index.checked_sub(k - 1)?)? / k;
+        }
+        value = value.checked_add(binomial.checked_mul(difference)?)?;
+    }
+    return Some(value);
+}
+
+/// Same as [`checked_value_at`], but with arbitrary precision.
+fn big_value_at(history: &[i64], index: i64) -> BigInt {
+    let index = BigInt::from(index);
+    let mut current = history
+        .iter()
+        .copied()
+        .map(BigInt::from)
+        .collect::<Vec<_>>();
+    let mut binomial = BigInt::one();
+    let mut value = BigInt::zero();
+    let mut k = 0;
+    while !current.iter().all(Zero::is_zero) {
+        if k > 0 {
+            binomial = binomial * (&index - (k - 1)) / k;
+        }
+        value += &binomial * &current[0];
+        current = current.windows(2).map(|pair| &pair[1] - &pair[0]).collect();
+        k += 1;
+    }
+    return value;
+}
+
+fn value_at(history: &[i64], index: i64) -> Value {
+    return match checked_value_at(history, index) {
+        Some(value) => Value::Small(value),
+        None => Value::Big(big_value_at(history, index)),
+    };
+}
+
+fn next_values(history: &[i64], count: usize) -> Vec<Value> {
+    let start = history.len() as i64;
+    return (start..start + count as i64)
+        .map(|index| value_at(history, index))
+        .collect();
+}
+
+fn previous_values(history: &[i64], count: usize) -> Vec<Value> {
+    return (1..=count as i64)
+        .map(|offset| value_at(history, -offset))
+        .collect();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HistoryError {
+    InvalidNumber(String),
+    /// Fewer than two values, so there is no difference to extrapolate from.
+    Underdetermined,
+    /// The difference pyramid ran out of rows before reaching one made of
+    /// zeros, so the history does not pin down a polynomial.
+    NotReducible,
+    /// A difference does not fit in an `i64`.
+    Overflow,
+}
+
+impl Display for HistoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistoryError::InvalidNumber(n) => write!(f, "invalid number {:?}", n),
+            HistoryError::Underdetermined => write!(f, "underdetermined: fewer than two values"),
+            HistoryError::NotReducible => write!(f, "not reducible: no all-zero difference row"),
+            HistoryError::Overflow => write!(f, "difference overflowed"),
+        }
+    }
+}
+
+impl std::error::Error for HistoryError {}
+
+/// One-based line number of the offending history.
+type LineError = (usize, HistoryError);
+
+/// Degree of the polynomial generating `history`.
+fn classify(history: &[i64]) -> Result<usize, HistoryError> {
+    if history.len() < 2 {
+        return Err(HistoryError::Underdetermined);
+    }
+    let differences = forward_differences(history).ok_or(HistoryError::Overflow)?;
+    if differences.len() == history.len() {
+        return Err(HistoryError::NotReducible);
+    }
+    return Ok(differences.len().saturating_sub(1));
+}
+
+/// Like `classify`, but settles overflowing differences with exact
+/// arithmetic, as `value_at` does, instead of giving up.
+fn exact_degree(history: &[i64]) -> Result<usize, HistoryError> {
+    return match classify(history) {
+        Err(HistoryError::Overflow) => fit_polynomial(history).map(|p| p.degree()),
+        result => result,
+    };
+}
+
+fn diagnose(input: &str) -> Vec<(usize, Result<usize, HistoryError>)> {
+    return input
         .lines()
-        .map(|line| {
-            line.split_whitespace()
-                .map(|n| n.parse::<i64>().unwrap())
-                .collect::<Vec<_>>()
+        .enumerate()
+        .map(|(index, line)| {
+            (
+                index + 1,
+                parse_history(line).and_then(|h| exact_degree(&h)),
+            )
         })
-        .map(|starting_numbers| {
-            let mut stack = vec![starting_numbers.clone()];
-            let mut current = starting_numbers;
-            while !current.iter().all(|n| *n == 0) {
-                let mut new = Vec::with_capacity(current.len() - 1);
-                for i in 0..current.len() - 1 {
-                    let a = current[i];
-                    let b = current[i + 1];
-                    new.push(b - a);
-                }
-                stack.push(new.clone());
-                current = new;
+        .collect();
+}
+
+/// Every row of the difference pyramid, down to the first all-zero row.
+fn difference_pyramid(history: Vec<i64>) -> Result<Vec<Vec<i64>>, HistoryError> {
+    classify(&history)?;
+    let mut stack = vec![history];
+    while let Some(current) = stack.last().filter(|row| !row.iter().all(|n| *n == 0)) {
+        let new = current.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        stack.push(new);
+    }
+    return Ok(stack);
+}
+
+/// Polynomial coefficients, from the constant term up to the leading one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Polynomial(Vec<BigRational>);
+
+impl Polynomial {
+    fn degree(&self) -> usize {
+        return self.0.len().saturating_sub(1);
+    }
+}
+
+impl Display for Polynomial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut first = true;
+        for (power, coefficient) in self.0.iter().enumerate().rev() {
+            if coefficient.is_zero() {
+                continue;
             }
+            let sign = match (first, coefficient.is_negative()) {
+                (true, true) => "-",
+                (true, false) => "",
+                (false, true) => " - ",
+                (false, false) => " + ",
+            };
+            first = false;
+            let magnitude = coefficient.abs();
+            let magnitude = match (power, magnitude.is_integer()) {
+                (0, _) => magnitude.to_string(),
+                (_, true) if magnitude.is_one() => String::new(),
+                (_, true) => magnitude.to_string(),
+                (_, false) => format!("({})", magnitude),
+            };
+            let variable = match power {
+                0 => String::new(),
+                1 => "x".to_string(),
+                _ => format!("x^{}", power),
+            };
+            write!(f, "{}{}{}", sign, magnitude, variable)?;
+        }
+        if first {
+            write!(f, "0")?;
+        }
+        return Ok(());
+    }
+}
+
+/// Finds the minimal-degree polynomial `p` with `p(i) == history[i]`.
+///
+/// Newton's form `sum(differences[k] * x(x-1)...(x-k+1) / k!)` is expanded
+/// into plain coefficients.
+fn fit_polynomial(history: &[i64]) -> Result<Polynomial, HistoryError> {
+    if history.len() < 2 {
+        return Err(HistoryError::Underdetermined);
+    }
+    let mut current = history
+        .iter()
+        .copied()
+        .map(BigInt::from)
+        .collect::<Vec<_>>();
+    let mut coefficients = vec![BigRational::zero()];
+    let mut falling_factorial = vec![BigRational::one()];
+    let mut factorial = BigInt::one();
+    let mut k = 0;
+    while !current.iter().all(Zero::is_zero) {
+        if k > 0 {
+            factorial *= k;
+            let mut next = vec![BigRational::zero(); falling_factorial.len() + 1];
+            for (power, coefficient) in falling_factorial.iter().enumerate() {
+                next[power + 1] += coefficient;
+                next[power] -= coefficient * BigRational::from_integer((k - 1).into());
+            }
+            falling_factorial = next;
+        }
+        let scale = BigRational::new(current[0].clone(), factorial.clone());
+        coefficients.resize(falling_factorial.len(), BigRational::zero());
+        for (power, coefficient) in falling_factorial.iter().enumerate() {
+            coefficients[power] += coefficient * &scale;
+        }
+        current = current.windows(2).map(|pair| &pair[1] - &pair[0]).collect();
+        k += 1;
+    }
+    if current.is_empty() {
+        return Err(HistoryError::NotReducible);
+    }
+    while coefficients.len() > 1 && coefficients.last().is_some_and(Zero::is_zero) {
+        coefficients.pop();
+    }
+    return Ok(Polynomial(coefficients));
+}
+
+fn part1(input: &str) -> Result<i64, LineError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let stack = parse_history(line)
+                .and_then(difference_pyramid)
+                .map_err(|error| (index + 1, error))?;
             let mut result = 0;
             for list in stack.into_iter().rev() {
                 result += list.last().unwrap();
             }
-            return result;
+            return Ok(result);
         })
         .sum()
 }
-fn part2(input: &str) -> i64 {
+fn part2(input: &str) -> Result<i64, LineError> {
     input
         .lines()
-        .map(|line| {
-            line.split_whitespace()
-                .map(|n| n.parse::<i64>().unwrap())
-                .collect::<Vec<_>>()
-        })
-        .map(|starting_numbers| {
-            let mut stack = vec![starting_numbers.clone()];
-            let mut current = starting_numbers;
-            while !current.iter().all(|n| *n == 0) {
-                let mut new = Vec::with_capacity(current.len() - 1);
-                for i in 0..current.len() - 1 {
-                    let a = current[i];
-                    let b = current[i + 1];
-                    new.push(b - a);
-                }
-                stack.push(new.clone());
-                current = new;
-            }
+        .enumerate()
+        .map(|(index, line)| {
+            let stack = parse_history(line)
+                .and_then(difference_pyramid)
+                .map_err(|error| (index + 1, error))?;
             let mut result = 0;
             for list in stack.into_iter().rev() {
                 result = list.first().unwrap() - result;
             }
-            return result;
+            return Ok(result);
         })
         .sum()
 }
@@ -72,11 +368,85 @@ mod tests {
 
     #[test]
     fn part1() {
-        assert_eq!(super::part1(INPUT), 114);
+        assert_eq!(super::part1(INPUT), Ok(114));
     }
 
     #[test]
     fn part2() {
-        assert_eq!(super::part2(INPUT), 2);
+        assert_eq!(super::part2(INPUT), Ok(2));
+    }
+
+    #[test]
+    fn diagnose() {
+        use super::HistoryError::*;
+        let input = "0 3 6 9\n5 5\n7\n\n1 2 4 8\n1 x 3\n-9223372036854775808 1 2\n\
+                     9223372036854775807 -1 -1 9223372036854775807";
+        assert_eq!(
+            super::diagnose(input),
+            vec![
+                (1, Ok(1)),
+                (2, Ok(0)),
+                (3, Err(Underdetermined)),
+                (4, Err(Underdetermined)),
+                (5, Err(NotReducible)),
+                (6, Err(InvalidNumber("x".to_string()))),
+                (7, Err(NotReducible)),
+                (8, Ok(2)),
+            ]
+        );
+        assert_eq!(super::part1(input), Err((3, Underdetermined)));
+        assert_eq!(super::part2("1 2 4 8"), Err((1, NotReducible)));
+        assert_eq!(super::exact_degree(&[1, 2, 4, 8]), Err(NotReducible));
+        assert_eq!(super::exact_degree(&[7]), Err(Underdetermined));
+        assert_eq!(super::exact_degree(&[i64::MIN, 1, 2]), Err(NotReducible));
+        assert_eq!(super::exact_degree(&[0, 3, 6, 9]), Ok(1));
+    }
+
+    #[test]
+    fn extrapolate() {
+        use super::Value::Small;
+        let history = [10, 13, 16, 21, 30, 45];
+        assert_eq!(super::value_at(&history, 6), Small(68));
+        assert_eq!(super::value_at(&history, -1), Small(5));
+        assert_eq!(super::value_at(&history, 2), Small(16));
+        assert_eq!(
+            super::next_values(&[1, 3, 6, 10, 15, 21], 3),
+            vec![Small(28), Small(36), Small(45)]
+        );
+        assert_eq!(
+            super::previous_values(&[1, 3, 6, 10, 15, 21], 3),
+            vec![Small(0), Small(0), Small(1)]
+        );
+    }
+
+    #[test]
+    fn polynomial() {
+        let fit = |history: &[i64]| super::fit_polynomial(history).map(|p| p.to_string());
+        assert_eq!(fit(&[1, 0, 3, 10]), Ok("2x^2 - 3x + 1".to_string()));
+        assert_eq!(fit(&[0, 3, 6, 9, 12, 15]), Ok("3x".to_string()));
+        assert_eq!(
+            fit(&[1, 3, 6, 10, 15, 21]),
+            Ok("(1/2)x^2 + (3/2)x + 1".to_string())
+        );
+        assert_eq!(fit(&[-4, -5, -6]), Ok("-x - 4".to_string()));
+        assert_eq!(fit(&[0, 0]), Ok("0".to_string()));
+        assert_eq!(fit(&[1, 2, 4]), Err(super::HistoryError::NotReducible));
+
+        let polynomial = super::fit_polynomial(&[10, 13, 16, 21, 30, 45]).unwrap();
+        assert_eq!(polynomial.degree(), 3);
+        assert_eq!(polynomial.to_string(), "(1/3)x^3 - x^2 + (11/3)x + 10");
+    }
+
+    #[test]
+    fn extrapolate_overflow() {
+        let squares = [0, 1, 4, 9];
+        assert_eq!(
+            super::value_at(&squares, 4_000_000_000),
+            super::Value::Big("16000000000000000000".parse().unwrap())
+        );
+        assert_eq!(
+            super::value_at(&[i64::MIN, i64::MAX], 0),
+            super::Value::Big(i64::MIN.into())
+        );
     }
 }
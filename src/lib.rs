@@ -0,0 +1,3 @@
+//! Helpers shared between the daily binaries.
+
+pub mod random;
@@ -0,0 +1,31 @@
+//! Small deterministic pseudo-random numbers for benchmarks and tests, so
+//! they need no extra dependency.
+
+/// Marsaglia's xorshift64 generator.
+pub struct XorShift(u64);
+
+impl XorShift {
+    /// The seed must not be zero, or every number is zero.
+    pub fn new(seed: u64) -> Self {
+        assert_ne!(seed, 0, "xorshift needs a non-zero seed");
+        XorShift(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..bound`.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+impl Default for XorShift {
+    fn default() -> Self {
+        XorShift::new(0x2545_f491_4f6c_dd1d)
+    }
+}